@@ -0,0 +1,76 @@
+#![no_std]
+//! Mock SEP-40 price feed for local integration tests.
+//!
+//! Exposes the read interface the time-lock contract consumes
+//! (`lastprice`, `decimals`, `resolution`) plus unauthenticated setters so
+//! tests can drive prices and timestamps directly.
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Symbol};
+
+/// SEP-40 asset identifier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// SEP-40 price record
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+enum DataKey {
+    Decimals,
+    Resolution,
+    Price(Asset),
+}
+
+const DEFAULT_DECIMALS: u32 = 14;
+const DEFAULT_RESOLUTION: u32 = 300;
+
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    /// Set the feed precision and update interval (seconds)
+    pub fn configure(env: Env, decimals: u32, resolution: u32) {
+        env.storage().instance().set(&DataKey::Decimals, &decimals);
+        env.storage().instance().set(&DataKey::Resolution, &resolution);
+    }
+
+    /// Publish a price for an asset
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&DataKey::Price(asset), &PriceData { price, timestamp });
+    }
+
+    /// Remove the price for an asset so `lastprice` returns `None`
+    pub fn clear_price(env: Env, asset: Asset) {
+        env.storage().instance().remove(&DataKey::Price(asset));
+    }
+
+    pub fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().instance().get(&DataKey::Price(asset))
+    }
+
+    pub fn decimals(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Decimals)
+            .unwrap_or(DEFAULT_DECIMALS)
+    }
+
+    pub fn resolution(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Resolution)
+            .unwrap_or(DEFAULT_RESOLUTION)
+    }
+}
@@ -0,0 +1,88 @@
+#![no_std]
+//! Mock Soroswap-style router for local integration tests.
+//!
+//! Implements `swap_exact_tokens_for_tokens` for single-hop paths at a fixed
+//! rate set by the test. Output is paid from the router's own token balance,
+//! so tests control liquidity by minting to the router.
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, vec, Address, Env, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum RouterError {
+    /// Path is not a configured single-hop pair
+    UnsupportedPath = 1,
+    /// Output is below the caller's minimum
+    InsufficientOutputAmount = 2,
+    /// Router cannot pay the output amount
+    InsufficientLiquidity = 3,
+    /// Swap deadline has passed
+    ExpiredDeadline = 4,
+}
+
+#[contracttype]
+enum DataKey {
+    Rate(Address, Address),
+}
+
+/// Precision of configured rates (1_000_000 = 1.0)
+const RATE_PRECISION: i128 = 1_000_000;
+
+#[contract]
+pub struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    /// Set the output per input unit for a pair, with 6 decimals
+    pub fn set_rate(env: Env, token_in: Address, token_out: Address, rate: i128) {
+        env.storage()
+            .instance()
+            .set(&DataKey::Rate(token_in, token_out), &rate);
+    }
+
+    /// Swap `amount_in` of `path[0]` from `to` for `path[1]`, paid to `to`.
+    /// Returns the input and output amounts.
+    pub fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, RouterError> {
+        to.require_auth();
+
+        if env.ledger().timestamp() > deadline {
+            return Err(RouterError::ExpiredDeadline);
+        }
+        if path.len() != 2 {
+            return Err(RouterError::UnsupportedPath);
+        }
+        let token_in = path.get_unchecked(0);
+        let token_out = path.get_unchecked(1);
+        let rate: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Rate(token_in.clone(), token_out.clone()))
+            .ok_or(RouterError::UnsupportedPath)?;
+
+        let amount_out = amount_in * rate / RATE_PRECISION;
+        if amount_out < amount_out_min {
+            return Err(RouterError::InsufficientOutputAmount);
+        }
+
+        let router = env.current_contract_address();
+        let out_client = token::Client::new(&env, &token_out);
+        if out_client.balance(&router) < amount_out {
+            return Err(RouterError::InsufficientLiquidity);
+        }
+
+        token::Client::new(&env, &token_in).transfer(&to, &router, &amount_in);
+        out_client.transfer(&router, &to, &amount_out);
+
+        Ok(vec![&env, amount_in, amount_out])
+    }
+}
@@ -0,0 +1,148 @@
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec};
+
+use crate::errors::Error;
+
+/// Domain tag prefixed to every claim attestation payload
+pub const CLAIM_DOMAIN_TAG: &[u8] = b"zendvo:claim:v1";
+
+/// Attestor public key, tagged with its signature scheme
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttestorPublicKey {
+    Ed25519(BytesN<32>),
+    Secp256r1(BytesN<65>), // SEC-1 uncompressed point (P-256, e.g. HSM or WebAuthn passkey)
+    Secp256k1(BytesN<65>), // SEC-1 uncompressed point
+}
+
+/// Attestor signature over `claim_payload`, tagged with its signature scheme.
+/// ECDSA schemes sign the SHA-256 digest of the payload.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttestorProof {
+    Ed25519(BytesN<64>),
+    Secp256r1(BytesN<64>),      // Low-S normalized (r || s)
+    Secp256k1(BytesN<64>, u32), // (r || s) and recovery id
+}
+
+/// Registered attestation verification key
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationKey {
+    pub attestor_id: u32,         // Attestor this key belongs to; rotated keys share it
+    pub public_key: AttestorPublicKey,
+    pub active_from: u64,         // First timestamp the key is accepted
+    pub expires_at: Option<u64>,  // Last timestamp the key is accepted (None = no expiry)
+    pub revoked: bool,
+}
+
+impl AttestationKey {
+    /// Whether the key may verify attestations at the given timestamp
+    pub fn is_valid_at(&self, timestamp: u64) -> bool {
+        !self.revoked
+            && timestamp >= self.active_from
+            && self.expires_at.is_none_or(|expires_at| timestamp <= expires_at)
+    }
+}
+
+/// An attestation key together with its id, as returned by listings
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationKeyRecord {
+    pub key_id: u32,
+    pub key: AttestationKey,
+}
+
+/// How many distinct attestors must sign a claim
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestorPolicy {
+    pub threshold: u32,          // Signatures required for high-value gifts
+    pub high_value_amount: i128, // Gifts above this amount need `threshold` signatures
+}
+
+impl AttestorPolicy {
+    /// Number of distinct attestor signatures required for a gift amount
+    pub fn required_signatures(&self, amount: i128) -> u32 {
+        if amount > self.high_value_amount {
+            self.threshold
+        } else {
+            1
+        }
+    }
+}
+
+/// A signature over `claim_payload` by one registered attestation key
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestorSignature {
+    pub key_id: u32,
+    pub proof: AttestorProof,
+}
+
+/// Verification proof submitted with `claim_gift`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimAttestation {
+    pub nonce: BytesN<32>,                 // Unique per attestation, consumed on claim
+    pub expires_at: u64,                   // Last timestamp the attestation is valid
+    pub signatures: Vec<AttestorSignature>, // Ordered by ascending key id, one per attestor
+}
+
+/// Build the message each attestor signs for a claim.
+///
+/// Layout (all integers big-endian):
+/// `CLAIM_DOMAIN_TAG || network_id (32) || contract XDR || gift_id (u64) ||
+///  claimant XDR || recipient_phone_hash XDR || expires_at (u64) || nonce (32)`
+pub fn claim_payload(
+    env: &Env,
+    contract: &Address,
+    gift_id: u64,
+    claimant: &Address,
+    recipient_phone_hash: &String,
+    expires_at: u64,
+    nonce: &BytesN<32>,
+) -> Bytes {
+    let mut payload = Bytes::from_slice(env, CLAIM_DOMAIN_TAG);
+    payload.extend_from_array(&env.ledger().network_id().to_array());
+    payload.append(&contract.clone().to_xdr(env));
+    payload.extend_from_array(&gift_id.to_be_bytes());
+    payload.append(&claimant.clone().to_xdr(env));
+    payload.append(&recipient_phone_hash.clone().to_xdr(env));
+    payload.extend_from_array(&expires_at.to_be_bytes());
+    payload.extend_from_array(&nonce.to_array());
+    payload
+}
+
+/// Verify one attestor signature over a claim payload.
+///
+/// The proof scheme must match the registered key. Ed25519 and secp256r1
+/// verification trap on a bad signature; secp256k1 recovers the signer and
+/// compares it with the registered key.
+pub fn verify_signature(
+    env: &Env,
+    public_key: &AttestorPublicKey,
+    payload: &Bytes,
+    proof: &AttestorProof,
+) -> Result<(), Error> {
+    match (public_key, proof) {
+        (AttestorPublicKey::Ed25519(public_key), AttestorProof::Ed25519(signature)) => {
+            env.crypto().ed25519_verify(public_key, payload, signature);
+        }
+        (AttestorPublicKey::Secp256r1(public_key), AttestorProof::Secp256r1(signature)) => {
+            let digest = env.crypto().sha256(payload);
+            env.crypto().secp256r1_verify(public_key, &digest, signature);
+        }
+        (
+            AttestorPublicKey::Secp256k1(public_key),
+            AttestorProof::Secp256k1(signature, recovery_id),
+        ) => {
+            let digest = env.crypto().sha256(payload);
+            let recovered = env.crypto().secp256k1_recover(&digest, signature, *recovery_id);
+            if &recovered != public_key {
+                return Err(Error::InvalidProof);
+            }
+        }
+        _ => return Err(Error::SignatureSchemeMismatch),
+    }
+    Ok(())
+}
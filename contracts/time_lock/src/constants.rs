@@ -1,3 +1,21 @@
 pub const GIFT_FEE_BPS: u32 = 200; 
 pub const MIN_GIFT_AMOUNT: i128 = 5_000_000; 
 pub const MAX_GIFT_AMOUNT: i128 = 1_000_000_000;
+pub const DEFAULT_CANCELLATION_WINDOW: u64 = 86_400; // 24 hours after creation
+pub const DEFAULT_CANCELLATION_FEE_BPS: u32 = 0;
+
+// Storage TTLs (in ledgers, ~5 seconds each)
+pub const LEDGER_SECONDS: u64 = 5;
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+pub const GIFT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+
+pub const MAX_PAGE_SIZE: u32 = 50; // Maximum gifts returned by a listing call
+pub const MAX_ATTESTATION_VALIDITY: u64 = 86_400; // Attestations expire within 24 hours
+pub const DEFAULT_ATTESTOR_THRESHOLD: u32 = 1;
+pub const DEFAULT_HIGH_VALUE_AMOUNT: i128 = MAX_GIFT_AMOUNT; // Single signer for all gifts until configured
+pub const PRICE_HISTORY_SIZE: u32 = 48; // Observations kept per currency pair
+pub const FIAT_ESCROW_BUFFER_BPS: u32 = 1000; // Extra escrow for fiat gifts against adverse FX moves
+pub const QUOTE_VALIDITY_SECS: u64 = 120; // How long a quote from get_quote can be acted on
+pub const FIAT_SETTLEMENT_GRACE_SECS: u64 = 7 * 86_400; // After unlock, fiat gifts then settle outside the creation-rate limit
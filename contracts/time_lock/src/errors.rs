@@ -1,21 +1,127 @@
 use soroban_sdk::contracterror;
 
+/// Contract error codes.
+///
+/// Codes are grouped by subsystem and are stable: new variants take the next
+/// free code in their group and existing codes are never reused.
+///
+/// - `1xx` gift lifecycle
+/// - `2xx` oracle
+/// - `3xx` slippage
+/// - `4xx` admin and configuration
+/// - `5xx` token and balances
+/// - `6xx` claim attestation
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum Error {
-    NotUnlocked = 1,
-    AlreadyClaimed = 2,
-    InvalidAmount = 3,
-    Unauthorized = 4,
-    GiftNotFound = 5,
-    InvalidStatus = 6,
-    InvalidProof = 7,
-    OracleUnavailable = 5,
-    StaleOracleData = 6,
-    InvalidExchangeRate = 7,
-    SlippageExceeded = 8,
-    InvalidSlippageConfig = 9,
-    OraclePaused = 10,
-    InsufficientLiquidity = 11,
+    // Gift lifecycle (1xx)
+    /// Gift amount is outside the allowed limits
+    InvalidAmount = 100,
+    /// No gift exists with the given id
+    GiftNotFound = 101,
+    /// Gift is not in a status that allows this operation
+    InvalidStatus = 102,
+    /// Gift unlock time has not been reached
+    UnlockTimeNotReached = 103,
+    /// Gift has already been claimed
+    AlreadyClaimed = 104,
+    /// Gift claim deadline has passed
+    GiftExpired = 105,
+    /// Gift has been refunded to its sender
+    GiftRefunded = 106,
+    /// Gift has been cancelled by its sender
+    GiftCancelled = 107,
+    /// Gift cannot be refunded yet (no deadline, or deadline not passed)
+    RefundNotAvailable = 108,
+    /// Claim deadline is not after the unlock time
+    InvalidClaimDeadline = 109,
+    /// Cancellation window has closed or the gift has unlocked
+    CancellationWindowClosed = 110,
+    /// Verification proof could not be validated. Only secp256k1 attestor
+    /// signatures return this; bad Ed25519 and secp256r1 signatures trap in
+    /// the host and surface as host crypto errors instead.
+    InvalidProof = 111,
+    /// Gift must be claimed by its recipient before it can be unlocked
+    GiftNotClaimed = 112,
+    /// Gift funds have already been released
+    AlreadyUnlocked = 113,
+
+    // Oracle (2xx)
+    /// Oracle could not provide a price
+    OracleUnavailable = 200,
+    /// Oracle price is older than the configured maximum age
+    StaleOracleData = 201,
+    /// Oracle returned an invalid exchange rate
+    InvalidExchangeRate = 202,
+    /// Oracle checks are paused
+    OraclePaused = 203,
+    /// Currency pair is not in the pair registry
+    UnsupportedCurrencyPair = 204,
+    /// Oracle sources, quorum or deviation limit are invalid
+    InvalidOracleConfig = 205,
+    /// Fewer oracle sources returned a valid price than the quorum requires
+    OracleQuorumNotMet = 206,
+    /// Oracle sources disagree by more than the allowed deviation
+    OracleDeviationExceeded = 207,
+    /// No recorded price covers the requested time
+    NoPriceHistory = 208,
+    /// TWAP window must be greater than zero
+    InvalidTwapWindow = 209,
+    /// Max oracle age or its bounds are out of range
+    InvalidOracleAge = 211,
+
+    // Slippage (3xx)
+    /// Rate deviation exceeds the slippage tolerance
+    SlippageExceeded = 300,
+    /// Slippage configuration value is out of bounds
+    InvalidSlippageConfig = 301,
+    /// Quote expiry supplied with the transaction has passed
+    QuoteExpired = 302,
+
+    // Admin and configuration (4xx)
+    /// Caller is not authorized for this operation
+    Unauthorized = 400,
+    /// Contract has not been initialized
+    NotInitialized = 401,
+    /// Contract has already been initialized
+    AlreadyInitialized = 402,
+    /// Cancellation policy value is out of bounds
+    InvalidCancellationPolicy = 403,
+
+    // Token and balances (5xx)
+    /// Not enough liquidity to complete the operation
+    InsufficientLiquidity = 500,
+    /// Withdrawal exceeds the collected fee balance
+    InsufficientFeeBalance = 501,
+    /// Sender balance cannot cover the deposit
+    InsufficientBalance = 502,
+    /// No swap route is configured for the requested payout token
+    UnsupportedPayoutToken = 503,
+    /// Swap route does not lead from the gift token to the payout token
+    InvalidPayoutRoute = 504,
+
+    // Claim attestation (6xx)
+    /// Attestation expiry has passed
+    AttestationExpired = 600,
+    /// Attestation nonce has already been consumed
+    NonceAlreadyUsed = 601,
+    /// Attestation expiry is further out than the maximum validity
+    AttestationValidityTooLong = 602,
+    /// No attestation key is registered with the given id
+    AttestationKeyNotFound = 603,
+    /// Attestation key is revoked, expired or not yet active
+    AttestationKeyInactive = 604,
+    /// Attestation key validity window is invalid
+    InvalidAttestationKey = 605,
+    /// Attestor threshold is zero or exceeds the registered attestors
+    InvalidAttestorPolicy = 606,
+    /// Attestor signatures are not from distinct attestors in ascending key id order
+    DuplicateAttestor = 607,
+    /// Fewer attestor signatures than the gift amount requires
+    InsufficientAttestations = 608,
+    /// Attestor proof scheme does not match the registered key
+    SignatureSchemeMismatch = 609,
+    /// Public key is already registered and not revoked
+    AttestationKeyExists = 610,
 }
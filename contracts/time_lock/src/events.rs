@@ -1,12 +1,18 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, String, Vec};
+
+use crate::attestation::AttestorPublicKey;
+use crate::oracle::Asset;
+use crate::slippage::{SlippageDirection, SlippageLimit};
+use crate::swap::PayoutRoute;
 
 /// Event emitted when oracle rate is queried
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct OracleRateQueried {
-    pub timestamp: u64,
-    pub rate: i128,
-    pub source: String,
+    pub pair: String,
+    pub timestamp: u64,          // Oldest feed timestamp among contributing sources
+    pub rate: i128,              // Median of the contributing sources
+    pub sources: Vec<Address>,   // Sources whose prices contributed to the median
 }
 
 /// Event emitted when slippage config is updated
@@ -25,6 +31,36 @@ pub struct SlippageCheckFailed {
     pub expected_rate: i128,
     pub actual_rate: i128,
     pub threshold: u32,
+    pub binding_limit: SlippageLimit, // Setting that supplied the threshold
+    pub direction: SlippageDirection,
+}
+
+/// Event emitted when the volatility-adjusted slippage settings are updated
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DynamicSlippageUpdated {
+    pub volatility_multiplier_bps: u32,
+    pub min_dynamic_bps: u32,
+    pub max_dynamic_bps: u32,
+    pub admin: Address,
+}
+
+/// Event emitted when the favourable slippage limit is updated
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FavourableSlippageUpdated {
+    pub old_limit: Option<u32>,
+    pub new_limit: Option<u32>,
+    pub admin: Address,
+}
+
+/// Event emitted when a currency pair's slippage limit is set or cleared
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PairSlippageUpdated {
+    pub pair: String,
+    pub max_slippage_bps: Option<u32>,
+    pub admin: Address,
 }
 
 /// Event emitted when oracle address is updated
@@ -35,8 +71,243 @@ pub struct OracleAddressUpdated {
     pub new_address: Address,
 }
 
+/// Event emitted when a protocol fee is collected on gift creation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeCollected {
+    pub gift_id: u64,
+    pub fee: i128,
+    pub fee_balance: i128,
+}
+
+/// Event emitted when protocol fees are withdrawn
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesWithdrawn {
+    pub to: Address,
+    pub amount: i128,
+    pub remaining_balance: i128,
+    pub admin: Address,
+}
+
+/// Event emitted when a claimed gift unlocks and funds are released
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftUnlocked {
+    pub gift_id: u64,
+    pub recipient: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a fiat-denominated gift is settled at unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiatGiftSettled {
+    pub gift_id: u64,
+    pub fiat_amount: i128,
+    pub rate: i128,          // Settlement rate (fiat per token)
+    pub payout: i128,        // Token amount paid to the recipient
+    pub sender_refund: i128, // Excess escrow returned to the sender
+}
+
+/// Event emitted when a fiat gift settles after the grace period for less
+/// than the fiat amount, because the escrow could not cover it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiatSettlementCapped {
+    pub gift_id: u64,
+    pub owed: i128,   // Token value of the fiat amount at the settlement rate
+    pub payout: i128, // Token amount paid, capped at the escrow
+}
+
+/// Event emitted when a payout is swapped into another token at unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutSwapped {
+    pub gift_id: u64,
+    pub payout_token: Address,
+    pub amount_in: i128,      // Gift token sent to the router
+    pub amount_out: i128,     // Payout token received by the recipient
+    pub executed_rate: i128,  // Payout per gift token achieved by the swap
+    pub oracle_rate: i128,    // Oracle rate the swap was guarded against
+}
+
+/// Event emitted when the swap router is set
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapRouterUpdated {
+    pub old_router: Option<Address>,
+    pub new_router: Address,
+    pub admin: Address,
+}
+
+/// Event emitted when a payout route is added or replaced
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutRouteUpdated {
+    pub payout_token: Address,
+    pub route: PayoutRoute,
+    pub admin: Address,
+}
+
+/// Event emitted when a payout route is removed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutRouteRemoved {
+    pub payout_token: Address,
+    pub admin: Address,
+}
+
+/// Event emitted when an unclaimed gift passes its claim deadline
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftExpired {
+    pub gift_id: u64,
+    pub claim_deadline: u64,
+}
+
+/// Event emitted when an expired gift is refunded to its sender
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftRefunded {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+}
+
+/// Event emitted when a sender cancels a gift before unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftCancelled {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub refunded_amount: i128,
+    pub cancellation_fee: i128,
+}
+
+/// Event emitted when the cancellation policy is updated
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationPolicyUpdated {
+    pub window_secs: u64,
+    pub fee_bps: u32,
+    pub admin: Address,
+}
+
+/// Event emitted when an attestation key is registered
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationKeyAdded {
+    pub key_id: u32,
+    pub attestor_id: u32,
+    pub public_key: AttestorPublicKey,
+    pub active_from: u64,
+    pub expires_at: Option<u64>,
+}
+
+/// Event emitted when an attestation key is revoked
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationKeyRevoked {
+    pub key_id: u32,
+    pub admin: Address,
+}
+
+/// Event emitted when the attestor threshold policy changes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestorPolicyUpdated {
+    pub threshold: u32,
+    pub high_value_amount: i128,
+    pub admin: Address,
+}
+
+/// Event emitted when a currency pair is registered or updated
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CurrencyPairUpdated {
+    pub pair: String,
+    pub asset: Asset,
+    pub max_oracle_age: Option<u64>,
+    pub admin: Address,
+}
+
+/// Event emitted when a currency pair is removed from the registry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CurrencyPairRemoved {
+    pub pair: String,
+    pub admin: Address,
+}
+
+/// Event emitted when the oracle source set changes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleSourcesUpdated {
+    pub sources: Vec<Address>,
+    pub min_quorum: u32,
+    pub max_deviation_bps: u32,
+    pub admin: Address,
+}
+
+/// Event emitted when a rate jump trips the circuit breaker and pauses oracle checks
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerTripped {
+    pub pair: String,
+    pub previous_rate: i128,
+    pub new_rate: i128,
+    pub deviation_bps: i128,
+    pub threshold_bps: u32,
+}
+
+/// Event emitted when the circuit breaker threshold is updated
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerUpdated {
+    pub old_threshold_bps: u32,
+    pub new_threshold_bps: u32,
+    pub admin: Address,
+}
+
+/// Event emitted when a source's price is dropped for being older than the max age
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleDataStale {
+    pub pair: String,
+    pub source: Address,
+    pub data_timestamp: u64,
+    pub max_age: u64,
+}
+
 /// Event topics
 pub const EVENT_ORACLE_RATE_QUERIED: &[u8] = b"OracleRateQueried";
 pub const EVENT_SLIPPAGE_CONFIG_UPDATED: &[u8] = b"SlippageConfigUpdated";
 pub const EVENT_SLIPPAGE_CHECK_FAILED: &[u8] = b"SlippageCheckFailed";
+pub const EVENT_PAIR_SLIPPAGE_UPDATED: &[u8] = b"PairSlippageUpdated";
+pub const EVENT_FAVOURABLE_SLIPPAGE_UPDATED: &[u8] = b"FavourableSlippageUpdated";
+pub const EVENT_DYNAMIC_SLIPPAGE_UPDATED: &[u8] = b"DynamicSlippageUpdated";
 pub const EVENT_ORACLE_ADDRESS_UPDATED: &[u8] = b"OracleAddressUpdated";
+pub const EVENT_FEE_COLLECTED: &[u8] = b"FeeCollected";
+pub const EVENT_FEES_WITHDRAWN: &[u8] = b"FeesWithdrawn";
+pub const EVENT_GIFT_UNLOCKED: &[u8] = b"GiftUnlocked";
+pub const EVENT_FIAT_GIFT_SETTLED: &[u8] = b"FiatGiftSettled";
+pub const EVENT_FIAT_SETTLEMENT_CAPPED: &[u8] = b"FiatSettlementCapped";
+pub const EVENT_PAYOUT_SWAPPED: &[u8] = b"PayoutSwapped";
+pub const EVENT_SWAP_ROUTER_UPDATED: &[u8] = b"SwapRouterUpdated";
+pub const EVENT_PAYOUT_ROUTE_UPDATED: &[u8] = b"PayoutRouteUpdated";
+pub const EVENT_PAYOUT_ROUTE_REMOVED: &[u8] = b"PayoutRouteRemoved";
+pub const EVENT_GIFT_EXPIRED: &[u8] = b"GiftExpired";
+pub const EVENT_GIFT_REFUNDED: &[u8] = b"GiftRefunded";
+pub const EVENT_GIFT_CANCELLED: &[u8] = b"GiftCancelled";
+pub const EVENT_CANCELLATION_POLICY_UPDATED: &[u8] = b"CancellationPolicyUpdated";
+pub const EVENT_ATTESTATION_KEY_ADDED: &[u8] = b"AttestationKeyAdded";
+pub const EVENT_ATTESTATION_KEY_REVOKED: &[u8] = b"AttestationKeyRevoked";
+pub const EVENT_ATTESTOR_POLICY_UPDATED: &[u8] = b"AttestorPolicyUpdated";
+pub const EVENT_CURRENCY_PAIR_UPDATED: &[u8] = b"CurrencyPairUpdated";
+pub const EVENT_CURRENCY_PAIR_REMOVED: &[u8] = b"CurrencyPairRemoved";
+pub const EVENT_ORACLE_SOURCES_UPDATED: &[u8] = b"OracleSourcesUpdated";
+pub const EVENT_CIRCUIT_BREAKER_TRIPPED: &[u8] = b"CircuitBreakerTripped";
+pub const EVENT_CIRCUIT_BREAKER_UPDATED: &[u8] = b"CircuitBreakerUpdated";
+pub const EVENT_ORACLE_DATA_STALE: &[u8] = b"OracleDataStale";
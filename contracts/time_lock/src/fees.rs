@@ -0,0 +1,28 @@
+use crate::constants::GIFT_FEE_BPS;
+
+/// Calculate a basis-point fee on an amount
+pub fn calculate_bps_fee(amount: i128, fee_bps: u32) -> i128 {
+    amount
+        .checked_mul(fee_bps as i128)
+        .unwrap_or(0)
+        / 10000
+}
+
+/// Calculate the protocol fee owed on a gross deposit
+pub fn calculate_fee(gross_amount: i128) -> i128 {
+    calculate_bps_fee(gross_amount, GIFT_FEE_BPS)
+}
+
+/// Split a gross deposit into the net gift amount and the protocol fee
+pub fn split_fee(gross_amount: i128) -> (i128, i128) {
+    let fee = calculate_fee(gross_amount);
+    (gross_amount - fee, fee)
+}
+
+/// Gross deposit whose net amount after the protocol fee covers
+/// `net_amount`. Returns `None` on overflow.
+pub fn gross_for_net(net_amount: i128) -> Option<i128> {
+    let denominator = 10000 - GIFT_FEE_BPS as i128;
+    let numerator = net_amount.checked_mul(10000)?;
+    Some((numerator + denominator - 1) / denominator)
+}
@@ -1,63 +1,509 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env,
-    String,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contract, contractimpl, contracttype, panic_with_error, symbol_short, token, vec, Address, BytesN, Env,
+    IntoVal, Map, String, Symbol, Vec,
 };
 
-mod types;
-mod errors;
-mod constants;
-mod oracle;
-mod slippage;
-mod events;
+pub mod types;
+pub mod errors;
+pub mod constants;
+pub mod oracle;
+pub mod slippage;
+pub mod events;
+pub mod fees;
+pub mod attestation;
+pub mod swap;
 mod test;
+mod simple_test;
 
-use types::{Gift, GiftStatus};
+use types::{
+    CancellationPolicy, ContractConfig, FiatDenomination, FiatGiftOutcome, Gift, GiftDenomination,
+    GiftPage, GiftRecord, GiftStatus, UnlockOutcome,
+};
 use errors::Error;
+use attestation::{
+    AttestationKey, AttestationKeyRecord, AttestorPolicy, AttestorPublicKey, ClaimAttestation,
+};
 
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
-    Gift(u64),
+    Config,       // Stores ContractConfig
+    OracleConfig, // Stores OracleConfig
+    SlippageConfig, // Stores SlippageConfig
+    CurrencyPairs, // Stores Map<String, PairConfig>
+    PriceCache(String), // Stores PriceCache per currency pair (temporary storage)
+    PriceHistory(String), // Stores Vec<PricePoint> per currency pair (persistent storage)
+    CircuitBreakerTrip, // Stores the CircuitBreakerTrip awaiting admin resume
+    Gift(u64),    // Stores Gift (persistent storage)
+    GiftIndexLen(GiftIndex),        // Stores u64 count of slots ever appended to an index
+    GiftIndexEntry(GiftIndex, u64), // Stores the gift id in one index slot (removed when delisted)
+    GiftIndexSlots(u64),            // Stores IndexSlots: a gift's slot in each index
+    UsedNonce(BytesN<32>),   // Marks a consumed claim attestation nonce
+    AttestationKeys,         // Stores Map<u32, AttestationKey>
+    NextAttestationKeyId,
+    AttestorPolicy,
     NextGiftId,
-    Oracle, // Stores BytesN<32> (Ed25519 Public Key)
-use errors::Error;
-use oracle::OracleConfig;
-use slippage::SlippageConfig;
+    FeeBalance, // Stores i128 of protocol fees held for the treasury
+    CancellationPolicy,
+    SwapRouter,   // Stores the Address of the payout swap router
+    PayoutRoutes, // Stores Map<Address, PayoutRoute> keyed by payout token
+}
+
+/// Secondary gift listings, each stored as one entry per slot
+#[contracttype]
+#[derive(Clone)]
+enum GiftIndex {
+    Sender(Address),   // Gifts created by a sender
+    PhoneHash(String), // Unclaimed gifts waiting for a phone hash
+}
+
+/// Slots a gift occupies in the secondary indexes
+#[contracttype]
+#[derive(Clone)]
+struct IndexSlots {
+    sender: u64,
+    phone_hash: u64,
+}
+
+use oracle::{Asset, CircuitBreakerTrip, OracleConfig, PairConfig, PricePoint, RateStatus};
+use slippage::{QuoteOutcome, SlippageConfig, SlippageDirection, SlippageLimit, SlippageStatus};
+use swap::{PayoutRoute, SwapRouterClient};
 use events::*;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PriceCache {
     pub rate: i128,
-    pub timestamp: u64,
+    pub timestamp: u64,   // Feed timestamp of the cached price
+    pub valid_until: u64, // Feed timestamp plus its resolution
 }
 
 #[contract]
 pub struct TimeLockContract;
 
+/// Helper: Get contract configuration from storage
+fn get_config_internal(env: &Env) -> Result<ContractConfig, Error> {
+    env.storage()
+        .instance()
+        .get::<_, ContractConfig>(&DataKey::Config)
+        .ok_or(Error::NotInitialized)
+}
+
 /// Helper: Get admin from storage
 fn get_admin(env: &Env) -> Result<Address, Error> {
-    env.storage()
-        .persistent()
-        .get::<_, Address>(&symbol_short!("admin"))
-        .ok_or(Error::Unauthorized)
+    get_config_internal(env).map(|config| config.admin)
 }
 
 /// Helper: Get oracle config from storage
 fn get_oracle_config(env: &Env) -> Result<OracleConfig, Error> {
+    env.storage()
+        .instance()
+        .get::<_, OracleConfig>(&DataKey::OracleConfig)
+        .ok_or(Error::NotInitialized)
+}
+
+/// Helper: Get the currency pair registry
+fn get_currency_pairs(env: &Env) -> Map<String, PairConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::CurrencyPairs)
+        .unwrap_or(Map::new(env))
+}
+
+/// Helper: Get the configuration of a supported currency pair
+fn get_pair_config(env: &Env, pair: &String) -> Result<PairConfig, Error> {
+    get_currency_pairs(env)
+        .get(pair.clone())
+        .ok_or(Error::UnsupportedCurrencyPair)
+}
+
+/// Helper: Drop cached rates so the next query reaches the oracle sources
+fn clear_price_caches(env: &Env) {
+    for pair in get_currency_pairs(env).keys().iter() {
+        env.storage().temporary().remove(&DataKey::PriceCache(pair));
+    }
+}
+
+/// Helper: Get the recorded rate history of a currency pair, oldest first
+fn load_price_history(env: &Env, pair: &String) -> Vec<PricePoint> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PriceHistory(pair.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Helper: Append an observation to a pair's bounded price history
+fn record_price(env: &Env, pair: &String, point: PricePoint) {
+    let key = DataKey::PriceHistory(pair.clone());
+    let mut history = load_price_history(env, pair);
+    if let Some(last) = history.last() {
+        if point.timestamp <= last.timestamp {
+            return;
+        }
+    }
+    history.push_back(point);
+    while history.len() > constants::PRICE_HISTORY_SIZE {
+        history.pop_front();
+    }
+    env.storage().persistent().set(&key, &history);
+    env.storage().persistent().extend_ttl(
+        &key,
+        constants::GIFT_BUMP_AMOUNT - constants::DAY_IN_LEDGERS,
+        constants::GIFT_BUMP_AMOUNT,
+    );
+}
+
+/// Helper: TWAP of a supported pair over a window ending now
+fn get_twap_internal(env: &Env, pair: &String, window_secs: u64) -> Result<i128, Error> {
+    get_pair_config(env, pair)?;
+    if window_secs == 0 {
+        return Err(Error::InvalidTwapWindow);
+    }
+    oracle::twap(&load_price_history(env, pair), env.ledger().timestamp(), window_secs)
+        .ok_or(Error::NoPriceHistory)
+}
+
+/// Helper: Escrow a gross deposit from an authorized sender and store the gift
+fn create_gift_internal(
+    env: &Env,
+    sender: Address,
+    amount: i128,
+    unlock_timestamp: u64,
+    recipient_phone_hash: String,
+    claim_deadline: Option<u64>,
+    denomination: GiftDenomination,
+) -> Result<u64, Error> {
+    // Check amount limits
+    if !(constants::MIN_GIFT_AMOUNT..=constants::MAX_GIFT_AMOUNT).contains(&amount) {
+        return Err(Error::InvalidAmount);
+    }
+
+    // A deadline must leave the recipient time to claim after unlock
+    if let Some(deadline) = claim_deadline {
+        if deadline <= unlock_timestamp {
+            return Err(Error::InvalidClaimDeadline);
+        }
+    }
+
+    let token = get_token_client(env)?;
+    if token.balance(&sender) < amount {
+        return Err(Error::InsufficientBalance);
+    }
+
+    let gift_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextGiftId)
+        .unwrap_or(1);
+
+    // Move the full deposit from the sender into escrow
+    token.transfer(&sender, &env.current_contract_address(), &amount);
+
+    // Deduct the protocol fee and credit it to the treasury balance
+    let (net_amount, fee) = fees::split_fee(amount);
+    let fee_balance = get_fee_balance_internal(env) + fee;
+    env.storage().instance().set(&DataKey::FeeBalance, &fee_balance);
+
+    env.events().publish(
+        (symbol_short!("fee_coll"),),
+        FeeCollected {
+            gift_id,
+            fee,
+            fee_balance,
+        },
+    );
+
+    let gift = Gift {
+        sender,
+        recipient: None,
+        amount: net_amount,
+        fee,
+        unlock_timestamp,
+        recipient_phone_hash,
+        claim_deadline,
+        created_at: env.ledger().timestamp(),
+        status: GiftStatus::Created,
+        denomination,
+    };
+
+    let slots = IndexSlots {
+        sender: index_add(env, GiftIndex::Sender(gift.sender.clone()), gift_id),
+        phone_hash: index_add(
+            env,
+            GiftIndex::PhoneHash(gift.recipient_phone_hash.clone()),
+            gift_id,
+        ),
+    };
     env.storage()
         .persistent()
-        .get::<_, OracleConfig>(&symbol_short!("oracle"))
-        .ok_or(Error::OracleUnavailable)
+        .set(&DataKey::GiftIndexSlots(gift_id), &slots);
+    save_gift(env, gift_id, &gift);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextGiftId, &(gift_id + 1));
+    extend_instance_ttl(env);
+
+    Ok(gift_id)
+}
+
+/// Helper: Spot rate for an FX-dependent operation, held within the slippage
+/// limit of the TWAP when a TWAP window is configured. A trip is returned as
+/// `RateStatus::Tripped` for the caller to report without rolling back.
+fn guarded_exchange_rate(
+    env: &Env,
+    currency_pair: &String,
+    gift_slippage_bps: Option<u32>,
+) -> Result<RateStatus, Error> {
+    let spot_rate = match query_exchange_rate(env, currency_pair)? {
+        RateStatus::Fresh(rate) => rate,
+        tripped => return Ok(tripped),
+    };
+    let slippage_config = get_slippage_config_internal(env)?;
+    if slippage_config.twap_window_secs > 0 {
+        let twap = get_twap_internal(env, currency_pair, slippage_config.twap_window_secs)?;
+        let limit = pair_slippage_limit(env, currency_pair, gift_slippage_bps)?;
+        check_slippage(env, twap, spot_rate, limit)?;
+    }
+    Ok(RateStatus::Fresh(spot_rate))
+}
+
+/// Helper: Effective slippage limit for a pair and optional gift tolerance
+fn pair_slippage_limit(
+    env: &Env,
+    currency_pair: &String,
+    gift_slippage_bps: Option<u32>,
+) -> Result<(u32, SlippageLimit), Error> {
+    let status = get_slippage_status(env, currency_pair)?;
+    Ok(slippage::effective_slippage_limit(
+        status.dynamic_bps,
+        status.pair_bps,
+        gift_slippage_bps,
+    ))
+}
+
+/// Helper: Slippage limit in effect for a pair and the inputs it derives from
+fn get_slippage_status(env: &Env, currency_pair: &String) -> Result<SlippageStatus, Error> {
+    let config = get_slippage_config_internal(env)?;
+    let pair_bps = get_pair_config(env, currency_pair)?.max_slippage_bps;
+    let volatility_bps =
+        oracle::realized_volatility_bps(&load_price_history(env, currency_pair)).unwrap_or(0);
+    let dynamic_bps = slippage::dynamic_slippage_bps(&config, volatility_bps);
+    let (effective_bps, binding_limit) =
+        slippage::effective_slippage_limit(dynamic_bps, pair_bps, None);
+    Ok(SlippageStatus {
+        config,
+        pair: currency_pair.clone(),
+        volatility_bps,
+        dynamic_bps,
+        pair_bps,
+        effective_bps,
+        binding_limit,
+    })
+}
+
+/// Helper: Get the rate of a pair from cache or the oracle sources. Sources
+/// whose own timestamp is older than the pair's max age are dropped with an
+/// `OracleDataStale` event. That event is only observable when the call
+/// still succeeds: if no fresh price remains it fails with `StaleOracleData`
+/// (even when other sources failed differently) and its events are
+/// discarded. A trip persists the pause, so it is returned as
+/// `RateStatus::Tripped` rather than an error that would roll it back.
+fn query_exchange_rate(env: &Env, currency_pair: &String) -> Result<RateStatus, Error> {
+    let mut oracle_config = get_oracle_config(env)?;
+    let pair_config = get_pair_config(env, currency_pair)?;
+    let max_age = pair_config.max_age(&oracle_config);
+    let cache_key = DataKey::PriceCache(currency_pair.clone());
+
+    if oracle_config.is_paused {
+        return Err(Error::OraclePaused);
+    }
+
+    let current_timestamp = env.ledger().timestamp();
+
+    // Try to get cached price first; it stays valid until the feed can
+    // have published a newer one
+    if let Some(cached) = env
+        .storage()
+        .temporary()
+        .get::<_, PriceCache>(&cache_key)
+    {
+        if current_timestamp < cached.valid_until
+            && oracle::validate_data_freshness(
+                current_timestamp,
+                cached.timestamp,
+                max_age,
+            )
+            .is_ok()
+        {
+            return Ok(RateStatus::Fresh(cached.rate));
+        }
+    }
+
+    // Query every source, dropping unavailable, stale or invalid responses
+    let mut sources = Vec::new(env);
+    let mut rates = Vec::new(env);
+    let mut oldest_timestamp = u64::MAX;
+    let mut valid_until = u64::MAX;
+    let mut last_error = Error::OracleUnavailable;
+    let mut any_stale = false;
+    for source in oracle_config.oracle_sources.iter() {
+        match oracle::fetch_source_price(env, &source, &pair_config.asset) {
+            Ok(price) => {
+                // Freshness is judged by the source's own timestamp
+                if oracle::validate_data_freshness(current_timestamp, price.timestamp, max_age)
+                    .is_err()
+                {
+                    env.events().publish(
+                        (symbol_short!("stale"),),
+                        OracleDataStale {
+                            pair: currency_pair.clone(),
+                            source,
+                            data_timestamp: price.timestamp,
+                            max_age,
+                        },
+                    );
+                    any_stale = true;
+                    continue;
+                }
+                oracle::insert_sorted(&mut rates, price.rate);
+                oldest_timestamp = oldest_timestamp.min(price.timestamp);
+                valid_until = valid_until.min(price.valid_until);
+                sources.push_back(source);
+            }
+            Err(err) => last_error = err,
+        }
+    }
+
+    if rates.is_empty() {
+        return Err(if any_stale { Error::StaleOracleData } else { last_error });
+    }
+    if rates.len() < oracle_config.min_quorum {
+        return Err(Error::OracleQuorumNotMet);
+    }
+
+    // Sources must agree within the configured spread
+    let lowest = rates.first().ok_or(Error::OracleUnavailable)?;
+    let highest = rates.last().ok_or(Error::OracleUnavailable)?;
+    if slippage::calculate_rate_difference(lowest, highest) > oracle_config.max_deviation_bps as i128 {
+        return Err(Error::OracleDeviationExceeded);
+    }
+
+    let oracle_rate = oracle::median(&rates).ok_or(Error::OracleUnavailable)?;
+
+    // Circuit breaker: a jump from the last recorded rate pauses oracle checks
+    if oracle_config.circuit_breaker_bps > 0 {
+        if let Some(previous) = load_price_history(env, currency_pair).last() {
+            let deviation_bps =
+                slippage::calculate_rate_difference(previous.rate, oracle_rate).abs();
+            if deviation_bps > oracle_config.circuit_breaker_bps as i128 {
+                let trip = CircuitBreakerTrip {
+                    pair: currency_pair.clone(),
+                    previous_rate: previous.rate,
+                    new_rate: oracle_rate,
+                    deviation_bps,
+                    threshold_bps: oracle_config.circuit_breaker_bps,
+                    timestamp: oldest_timestamp,
+                };
+                oracle_config.is_paused = true;
+                env.storage()
+                    .instance()
+                    .set(&DataKey::OracleConfig, &oracle_config);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::CircuitBreakerTrip, &trip);
+                clear_price_caches(env);
+
+                env.events().publish(
+                    (symbol_short!("cb_trip"),),
+                    CircuitBreakerTripped {
+                        pair: trip.pair.clone(),
+                        previous_rate: trip.previous_rate,
+                        new_rate: trip.new_rate,
+                        deviation_bps: trip.deviation_bps,
+                        threshold_bps: trip.threshold_bps,
+                    },
+                );
+
+                return Ok(RateStatus::Tripped(trip));
+            }
+        }
+    }
+
+    record_price(
+        env,
+        currency_pair,
+        PricePoint {
+            rate: oracle_rate,
+            timestamp: oldest_timestamp,
+        },
+    );
+
+    // Cache the rate
+    env.storage().temporary().set(
+        &cache_key,
+        &PriceCache {
+            rate: oracle_rate,
+            timestamp: oldest_timestamp,
+            valid_until,
+        },
+    );
+
+    env.events().publish(
+        (symbol_short!("price_q"),),
+        OracleRateQueried {
+            pair: currency_pair.clone(),
+            timestamp: oldest_timestamp,
+            rate: oracle_rate,
+            sources,
+        },
+    );
+
+    Ok(RateStatus::Fresh(oracle_rate))
+}
+
+/// Helper: Compare a rate against a reference and emit an event on failure.
+/// `max_slippage_bps` bounds unfavourable moves; favourable moves are bounded
+/// by the global favourable limit, if any.
+fn check_slippage(
+    env: &Env,
+    expected_rate: i128,
+    actual_rate: i128,
+    (max_slippage_bps, binding_limit): (u32, SlippageLimit),
+) -> Result<(), Error> {
+    let rate_diff = slippage::calculate_rate_difference(expected_rate, actual_rate);
+    let max_favourable_bps = get_slippage_config_internal(env)?.max_favourable_bps;
+
+    if let Some((direction, threshold)) =
+        slippage::exceeded_slippage_limit(rate_diff, max_slippage_bps, max_favourable_bps)
+    {
+        let binding_limit = match direction {
+            SlippageDirection::Favourable => SlippageLimit::Global,
+            SlippageDirection::Unfavourable => binding_limit,
+        };
+        env.events().publish(
+            (symbol_short!("slip_f"),),
+            SlippageCheckFailed {
+                expected_rate,
+                actual_rate,
+                threshold,
+                binding_limit,
+                direction,
+            },
+        );
+        return Err(Error::SlippageExceeded);
+    }
+
+    Ok(())
 }
 
 /// Helper: Get slippage config from storage
 fn get_slippage_config_internal(env: &Env) -> Result<SlippageConfig, Error> {
     env.storage()
-        .persistent()
-        .get::<_, SlippageConfig>(&symbol_short!("slippage"))
-        .ok_or(Error::Unauthorized)
+        .instance()
+        .get::<_, SlippageConfig>(&DataKey::SlippageConfig)
+        .ok_or(Error::NotInitialized)
 }
 
 /// Helper: Verify admin auth and return admin address
@@ -67,177 +513,1395 @@ fn require_admin_auth(env: &Env) -> Result<Address, Error> {
     Ok(admin)
 }
 
+/// Helper: Get the attestation key registry
+fn get_attestation_keys(env: &Env) -> Map<u32, AttestationKey> {
+    env.storage()
+        .instance()
+        .get(&DataKey::AttestationKeys)
+        .unwrap_or(Map::new(env))
+}
+
+/// Helper: Register a new attestation key and return its id
+fn add_attestation_key_internal(env: &Env, key: AttestationKey) -> Result<u32, Error> {
+    if let Some(expires_at) = key.expires_at {
+        if expires_at <= key.active_from {
+            return Err(Error::InvalidAttestationKey);
+        }
+    }
+
+    let mut keys = get_attestation_keys(env);
+    if keys
+        .values()
+        .iter()
+        .any(|existing| !existing.revoked && existing.public_key == key.public_key)
+    {
+        return Err(Error::AttestationKeyExists);
+    }
+
+    let key_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextAttestationKeyId)
+        .unwrap_or(1);
+
+    keys.set(key_id, key.clone());
+    env.storage().instance().set(&DataKey::AttestationKeys, &keys);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextAttestationKeyId, &(key_id + 1));
+
+    env.events().publish(
+        (symbol_short!("att_add"),),
+        AttestationKeyAdded {
+            key_id,
+            attestor_id: key.attestor_id,
+            public_key: key.public_key,
+            active_from: key.active_from,
+            expires_at: key.expires_at,
+        },
+    );
+
+    Ok(key_id)
+}
+
+/// Helper: Get an attestation key that is valid right now
+fn get_valid_attestation_key(env: &Env, key_id: u32) -> Result<AttestationKey, Error> {
+    let key = get_attestation_keys(env)
+        .get(key_id)
+        .ok_or(Error::AttestationKeyNotFound)?;
+    if !key.is_valid_at(env.ledger().timestamp()) {
+        return Err(Error::AttestationKeyInactive);
+    }
+    Ok(key)
+}
+
+/// Helper: Get the attestor threshold policy, falling back to defaults
+fn get_attestor_policy_internal(env: &Env) -> AttestorPolicy {
+    env.storage()
+        .instance()
+        .get(&DataKey::AttestorPolicy)
+        .unwrap_or(AttestorPolicy {
+            threshold: constants::DEFAULT_ATTESTOR_THRESHOLD,
+            high_value_amount: constants::DEFAULT_HIGH_VALUE_AMOUNT,
+        })
+}
+
+/// Helper: Get accumulated protocol fees held by the contract
+fn get_fee_balance_internal(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::FeeBalance)
+        .unwrap_or(0)
+}
+
+/// Helper: Get cancellation policy, falling back to defaults
+fn get_cancellation_policy_internal(env: &Env) -> CancellationPolicy {
+    env.storage()
+        .instance()
+        .get(&DataKey::CancellationPolicy)
+        .unwrap_or(CancellationPolicy {
+            window_secs: constants::DEFAULT_CANCELLATION_WINDOW,
+            fee_bps: constants::DEFAULT_CANCELLATION_FEE_BPS,
+        })
+}
+
+/// Helper: Load a gift by id
+fn load_gift(env: &Env, gift_id: u64) -> Result<Gift, Error> {
+    env.storage()
+        .persistent()
+        .get::<_, Gift>(&DataKey::Gift(gift_id))
+        .ok_or(Error::GiftNotFound)
+}
+
+/// Helper: Write a gift to persistent storage and keep it live
+fn save_gift(env: &Env, gift_id: u64, gift: &Gift) {
+    env.storage().persistent().set(&DataKey::Gift(gift_id), gift);
+    extend_gift_ttl_internal(env, gift_id, gift);
+}
+
+/// Helper: Extend a gift's TTL so it cannot be archived before it unlocks
+/// (or before its claim deadline), plus the standard bump period
+fn extend_gift_ttl_internal(env: &Env, gift_id: u64, gift: &Gift) {
+    let live_until = gift.claim_deadline.unwrap_or(gift.unlock_timestamp);
+    let ledgers_until = live_until.saturating_sub(env.ledger().timestamp()) / constants::LEDGER_SECONDS;
+    let extend_to = (ledgers_until.min(u32::MAX as u64) as u32)
+        .saturating_add(constants::GIFT_BUMP_AMOUNT)
+        .min(env.storage().max_ttl());
+
+    // Only rewrite the TTL once it has drifted a day below the target
+    let threshold = extend_to.saturating_sub(constants::DAY_IN_LEDGERS);
+    env.storage()
+        .persistent()
+        .extend_ttl(&DataKey::Gift(gift_id), threshold, extend_to);
+    extend_gift_index_ttl(env, gift_id, gift, threshold, extend_to);
+}
+
+/// Helper: Append a gift id to a secondary index and return its slot
+fn index_add(env: &Env, index: GiftIndex, gift_id: u64) -> u64 {
+    let len_key = DataKey::GiftIndexLen(index.clone());
+    let slot: u64 = env.storage().persistent().get(&len_key).unwrap_or(0);
+    let entry_key = DataKey::GiftIndexEntry(index, slot);
+    env.storage().persistent().set(&entry_key, &gift_id);
+    env.storage().persistent().set(&len_key, &(slot + 1));
+    extend_index_ttl(env, &len_key);
+    extend_index_ttl(env, &entry_key);
+    slot
+}
+
+/// Helper: Keep an index entry live for the standard bump period
+fn extend_index_ttl(env: &Env, key: &DataKey) {
+    if env.storage().persistent().has(key) {
+        env.storage().persistent().extend_ttl(
+            key,
+            constants::GIFT_BUMP_AMOUNT - constants::DAY_IN_LEDGERS,
+            constants::GIFT_BUMP_AMOUNT,
+        );
+    }
+}
+
+/// Helper: Keep a gift's index slots and their entries live as long as the gift
+fn extend_gift_index_ttl(env: &Env, gift_id: u64, gift: &Gift, threshold: u32, extend_to: u32) {
+    let slots_key = DataKey::GiftIndexSlots(gift_id);
+    let Some(slots) = env.storage().persistent().get::<_, IndexSlots>(&slots_key) else {
+        return;
+    };
+    let sender = GiftIndex::Sender(gift.sender.clone());
+    let phone_hash = GiftIndex::PhoneHash(gift.recipient_phone_hash.clone());
+    for key in [
+        slots_key,
+        DataKey::GiftIndexLen(sender.clone()),
+        DataKey::GiftIndexEntry(sender, slots.sender),
+        DataKey::GiftIndexLen(phone_hash.clone()),
+        DataKey::GiftIndexEntry(phone_hash, slots.phone_hash),
+    ] {
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
+
+/// Helper: Drop a gift from the unclaimed listing of its phone hash
+fn index_remove_phone_hash(env: &Env, gift_id: u64, phone_hash: &String) {
+    let slots: Option<IndexSlots> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::GiftIndexSlots(gift_id));
+    if let Some(slots) = slots {
+        env.storage().persistent().remove(&DataKey::GiftIndexEntry(
+            GiftIndex::PhoneHash(phone_hash.clone()),
+            slots.phone_hash,
+        ));
+    }
+}
+
+/// Helper: Read one page of gifts from a secondary index.
+/// `cursor` is the first slot to read; a page covers up to `limit` slots
+/// (clamped to `1..=MAX_PAGE_SIZE`) and skips delisted ones, so it may hold
+/// fewer records while `next_cursor` is still set.
+fn index_page(env: &Env, index: GiftIndex, cursor: u64, limit: u32) -> Result<GiftPage, Error> {
+    let len: u64 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::GiftIndexLen(index.clone()))
+        .unwrap_or(0);
+
+    let limit = limit.clamp(1, constants::MAX_PAGE_SIZE) as u64;
+    let end = cursor.saturating_add(limit).min(len);
+
+    let mut records = Vec::new(env);
+    for slot in cursor..end {
+        let entry: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GiftIndexEntry(index.clone(), slot));
+        if let Some(gift_id) = entry {
+            records.push_back(GiftRecord {
+                gift_id,
+                gift: load_gift(env, gift_id)?,
+            });
+        }
+    }
+
+    Ok(GiftPage {
+        records,
+        next_cursor: if end < len { Some(end) } else { None },
+    })
+}
+
+/// Helper: Keep contract instance (config, counters) live
+fn extend_instance_ttl(env: &Env) {
+    env.storage().instance().extend_ttl(
+        constants::INSTANCE_LIFETIME_THRESHOLD,
+        constants::INSTANCE_BUMP_AMOUNT,
+    );
+}
+
+/// Helper: Build a client for the configured gift token
+fn get_token_client(env: &Env) -> Result<token::Client<'_>, Error> {
+    let config = get_config_internal(env)?;
+    Ok(token::Client::new(env, &config.token))
+}
+
+/// Helper: Get the configured payout routes, keyed by payout token
+fn get_payout_routes(env: &Env) -> Map<Address, PayoutRoute> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PayoutRoutes)
+        .unwrap_or(Map::new(env))
+}
+
+/// Helper: Token value owed for a fiat gift at `rate` and the payout made.
+/// Until `FIAT_SETTLEMENT_GRACE_SECS` after unlock, the rate must stay within
+/// the slippage limit of the creation rate and the escrow must cover the
+/// fiat amount in full. After that the gift settles at any guarded rate,
+/// capped at the escrow, so its funds cannot be held indefinitely.
+fn fiat_payout(
+    env: &Env,
+    gift: &Gift,
+    denomination: &FiatDenomination,
+    rate: i128,
+) -> Result<(i128, i128), Error> {
+    let owed = oracle::fiat_to_token(denomination.fiat_amount, rate, false)
+        .ok_or(Error::InvalidExchangeRate)?;
+    let grace_ends = gift
+        .unlock_timestamp
+        .saturating_add(constants::FIAT_SETTLEMENT_GRACE_SECS);
+    if env.ledger().timestamp() > grace_ends {
+        return Ok((owed, owed.min(gift.amount)));
+    }
+
+    let limit = pair_slippage_limit(env, &denomination.pair, denomination.max_slippage_bps)?;
+    check_slippage(env, denomination.creation_rate, rate, limit)?;
+    if owed > gift.amount {
+        return Err(Error::SlippageExceeded);
+    }
+    Ok((owed, owed))
+}
+
+/// A payout swap, resolved before the unlock changes any state
+struct PayoutSwap {
+    payout_token: Address,
+    route: PayoutRoute,
+    router: Address,
+    oracle_rate: i128,             // Oracle rate of the route's pair
+    max_slippage_bps: Option<u32>, // Gift's own tolerance, if any
+}
+
+/// Helper: Route and router for swapping payouts into `payout_token`
+fn get_payout_swap_route(env: &Env, payout_token: &Address) -> Result<(PayoutRoute, Address), Error> {
+    let route = get_payout_routes(env)
+        .get(payout_token.clone())
+        .ok_or(Error::UnsupportedPayoutToken)?;
+    let router: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::SwapRouter)
+        .ok_or(Error::UnsupportedPayoutToken)?;
+    Ok((route, router))
+}
+
+/// Helper: Swap a payout of the gift token into `payout_token` through the
+/// router and send it to the recipient. The router's min-out is the expected
+/// output at the oracle rate less the slippage limit (or the
+/// recipient's `min_out`, if higher), and the executed rate is checked
+/// against the oracle rate. A router min-out failure surfaces as
+/// `SlippageExceeded` and a liquidity shortfall as `InsufficientLiquidity`.
+/// Returns the amount paid out.
+fn swap_payout(
+    env: &Env,
+    gift_id: u64,
+    recipient: &Address,
+    swap: PayoutSwap,
+    amount_in: i128,
+    min_out: Option<i128>,
+) -> Result<i128, Error> {
+    let PayoutSwap {
+        payout_token,
+        route,
+        router,
+        oracle_rate,
+        max_slippage_bps,
+    } = swap;
+    let limit = pair_slippage_limit(env, &route.currency_pair, max_slippage_bps)?;
+    let expected_min = slippage::calculate_expected_output(oracle_rate, amount_in, limit.0);
+    let amount_out_min = min_out.map_or(expected_min, |min_out| min_out.max(expected_min));
+
+    // The router pulls the input from this contract, one level below our call
+    let contract = env.current_contract_address();
+    let gift_token = get_config_internal(env)?.token;
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: gift_token,
+                fn_name: Symbol::new(env, "transfer"),
+                args: (contract.clone(), router.clone(), amount_in).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    let result = SwapRouterClient::new(env, &router).try_swap_exact_tokens_for_tokens(
+        &amount_in,
+        &amount_out_min,
+        &route.path,
+        &contract,
+        &env.ledger().timestamp(),
+    );
+    let amounts = match result {
+        Ok(Ok(amounts)) => amounts,
+        Err(Ok(err)) => match swap::map_router_error(&err) {
+            Some(mapped) => return Err(mapped),
+            // Unexpected router or host failures are re-raised as they are
+            None => panic_with_error!(env, err),
+        },
+        Ok(Err(err)) => panic_with_error!(env, err),
+        Err(Err(err)) => panic_with_error!(env, swap::invoke_error(err)),
+    };
+    let amount_out = amounts.last().ok_or(Error::InsufficientLiquidity)?;
+    if amount_out < amount_out_min {
+        return Err(Error::SlippageExceeded);
+    }
+
+    let executed_rate =
+        swap::executed_rate(amount_in, amount_out).ok_or(Error::InvalidExchangeRate)?;
+    check_slippage(env, oracle_rate, executed_rate, limit)?;
+
+    token::Client::new(env, &payout_token).transfer(&contract, recipient, &amount_out);
+
+    env.events().publish(
+        (symbol_short!("swapped"),),
+        PayoutSwapped {
+            gift_id,
+            payout_token,
+            amount_in,
+            amount_out,
+            executed_rate,
+            oracle_rate,
+        },
+    );
+
+    Ok(amount_out)
+}
+
 #[contractimpl]
 impl TimeLockContract {
-    pub fn initialize(env: Env, oracle_pk: BytesN<32>) {
-        if env.storage().instance().has(&DataKey::Oracle) {
-            panic!("Already initialized");
+    /// Initialize the contract: admin, attestation key, price oracle,
+    /// gift token and fee treasury are all set from a single config
+    pub fn initialize(env: Env, config: ContractConfig) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Config) {
+            return Err(Error::AlreadyInitialized);
         }
-        env.storage().instance().set(&DataKey::Oracle, &oracle_pk);
+
+        let oracle_config = oracle::default_oracle_config(&env, config.price_oracle.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::OracleConfig, &oracle_config);
+        env.storage()
+            .instance()
+            .set(&DataKey::CurrencyPairs, &oracle::default_pairs(&env));
+
+        let slippage_config = slippage::default_slippage_config(config.admin.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::SlippageConfig, &slippage_config);
+
+        add_attestation_key_internal(
+            &env,
+            AttestationKey {
+                attestor_id: 1,
+                public_key: AttestorPublicKey::Ed25519(config.attestation_pk.clone()),
+                active_from: env.ledger().timestamp(),
+                expires_at: None,
+                revoked: false,
+            },
+        )?;
+
+        env.storage().instance().set(&DataKey::Config, &config);
         env.storage().instance().set(&DataKey::NextGiftId, &1u64);
+
+        Ok(())
+    }
+
+    /// Get contract configuration (public view)
+    pub fn get_config(env: Env) -> Result<ContractConfig, Error> {
+        get_config_internal(&env)
+    }
+
+    pub fn create_gift(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        unlock_timestamp: u64,
+        recipient_phone_hash: String,
+        claim_deadline: Option<u64>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        create_gift_internal(
+            &env,
+            sender,
+            amount,
+            unlock_timestamp,
+            recipient_phone_hash,
+            claim_deadline,
+            GiftDenomination::Token,
+        )
+    }
+
+    /// Create a gift denominated in fiat (e.g. NGN 50,000) and settled in the
+    /// gift token at unlock. The escrow covers `fiat_amount` at the current
+    /// rate plus `FIAT_ESCROW_BUFFER_BPS`; the protocol fee is added on top.
+    /// `fiat_amount` uses the gift token's decimals. `max_slippage_bps`
+    /// optionally tightens the slippage limit applied to this gift's quotes.
+    /// A circuit breaker trip creates no gift and returns
+    /// `FiatGiftOutcome::Tripped`, so the pause is persisted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_fiat_gift(
+        env: Env,
+        sender: Address,
+        currency_pair: String,
+        fiat_amount: i128,
+        unlock_timestamp: u64,
+        recipient_phone_hash: String,
+        claim_deadline: Option<u64>,
+        max_slippage_bps: Option<u32>,
+    ) -> Result<FiatGiftOutcome, Error> {
+        sender.require_auth();
+
+        if fiat_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if let Some(bps) = max_slippage_bps {
+            slippage::validate_slippage_bounds(bps).map_err(|_| Error::InvalidSlippageConfig)?;
+        }
+
+        let rate = match guarded_exchange_rate(&env, &currency_pair, max_slippage_bps)? {
+            RateStatus::Fresh(rate) => rate,
+            RateStatus::Tripped(trip) => return Ok(FiatGiftOutcome::Tripped(trip)),
+        };
+        let token_amount =
+            oracle::fiat_to_token(fiat_amount, rate, true).ok_or(Error::InvalidExchangeRate)?;
+        let escrow = token_amount
+            + fees::calculate_bps_fee(token_amount, constants::FIAT_ESCROW_BUFFER_BPS);
+        let gross_amount = fees::gross_for_net(escrow).ok_or(Error::InvalidAmount)?;
+
+        let gift_id = create_gift_internal(
+            &env,
+            sender,
+            gross_amount,
+            unlock_timestamp,
+            recipient_phone_hash,
+            claim_deadline,
+            GiftDenomination::Fiat(FiatDenomination {
+                pair: currency_pair,
+                fiat_amount,
+                creation_rate: rate,
+                max_slippage_bps,
+            }),
+        )?;
+        Ok(FiatGiftOutcome::Created(gift_id))
+    }
+
+    /// Phase one: the recipient proves their identity and is bound to the
+    /// gift. Allowed before the unlock time; no funds move.
+    pub fn claim_gift(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        attestation: ClaimAttestation,
+    ) -> Result<(), Error> {
+        claimant.require_auth();
+
+        let mut gift = load_gift(&env, gift_id)?;
+
+        // Verify status
+        match gift.status {
+            GiftStatus::Created => {}
+            GiftStatus::Claimed => return Err(Error::AlreadyClaimed),
+            GiftStatus::Expired => return Err(Error::GiftExpired),
+            GiftStatus::Refunded => return Err(Error::GiftRefunded),
+            GiftStatus::Cancelled => return Err(Error::GiftCancelled),
+            _ => return Err(Error::InvalidStatus),
+        }
+
+        // Verify Claim Deadline
+        if gift.is_past_deadline(env.ledger().timestamp()) {
+            return Err(Error::GiftExpired);
+        }
+
+        // Verify attestation freshness and that it has not been used before
+        let now = env.ledger().timestamp();
+        if now > attestation.expires_at {
+            return Err(Error::AttestationExpired);
+        }
+        if attestation.expires_at > now.saturating_add(constants::MAX_ATTESTATION_VALIDITY) {
+            return Err(Error::AttestationValidityTooLong);
+        }
+        let nonce_key = DataKey::UsedNonce(attestation.nonce.clone());
+        if env.storage().persistent().has(&nonce_key) {
+            return Err(Error::NonceAlreadyUsed);
+        }
+
+        // Verify attestor signatures over the domain-separated payload
+        let required = get_attestor_policy_internal(&env).required_signatures(gift.amount);
+        if attestation.signatures.len() < required {
+            return Err(Error::InsufficientAttestations);
+        }
+        let payload = attestation::claim_payload(
+            &env,
+            &env.current_contract_address(),
+            gift_id,
+            &claimant,
+            &gift.recipient_phone_hash,
+            attestation.expires_at,
+            &attestation.nonce,
+        );
+        // Rotated keys share an attestor id, so each attestor counts once
+        let mut last_key_id: Option<u32> = None;
+        let mut attestor_ids: Vec<u32> = Vec::new(&env);
+        for attestor in attestation.signatures.iter() {
+            if last_key_id.is_some_and(|last| attestor.key_id <= last) {
+                return Err(Error::DuplicateAttestor);
+            }
+            last_key_id = Some(attestor.key_id);
+
+            let key = get_valid_attestation_key(&env, attestor.key_id)?;
+            if attestor_ids.contains(key.attestor_id) {
+                return Err(Error::DuplicateAttestor);
+            }
+            attestor_ids.push_back(key.attestor_id);
+            attestation::verify_signature(&env, &key.public_key, &payload, &attestor.proof)?;
+        }
+
+        // Consume the nonce; it only needs to outlive the attestation expiry
+        env.storage().persistent().set(&nonce_key, &true);
+        let nonce_ttl = (attestation.expires_at - now) / constants::LEDGER_SECONDS
+            + constants::DAY_IN_LEDGERS as u64;
+        env.storage()
+            .persistent()
+            .extend_ttl(&nonce_key, nonce_ttl as u32, nonce_ttl as u32);
+
+        // Update Gift
+        gift.recipient = Some(claimant.clone());
+        gift.status = GiftStatus::Claimed;
+
+        save_gift(&env, gift_id, &gift);
+        index_remove_phone_hash(&env, gift_id, &gift.recipient_phone_hash);
+        extend_instance_ttl(&env);
+
+        // Emit event
+        env.events().publish(
+            (symbol_short!("claimed"),),
+            (gift_id, claimant, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    /// Phase two: release the escrowed funds to the bound recipient once the
+    /// unlock time has passed.
+    ///
+    /// `min_out` is the least payout the recipient will accept, in the token
+    /// they receive, and `quote_expiry` the expiry of the quote it was derived
+    /// from; either may be omitted. With `payout_token` set, the payout is
+    /// swapped into that token through the configured router.
+    ///
+    /// Fiat gifts that settle outside the creation-rate limit or beyond the
+    /// escrow are refused until `FIAT_SETTLEMENT_GRACE_SECS` after unlock;
+    /// after that they settle with the payout capped at the escrow.
+    ///
+    /// If a rate query trips the circuit breaker, nothing is paid, the gift
+    /// stays claimed and `UnlockOutcome::Tripped` is returned so the pause
+    /// is persisted.
+    pub fn unlock_gift(
+        env: Env,
+        gift_id: u64,
+        recipient: Address,
+        min_out: Option<i128>,
+        quote_expiry: Option<u64>,
+        payout_token: Option<Address>,
+    ) -> Result<UnlockOutcome, Error> {
+        recipient.require_auth();
+
+        let mut gift = load_gift(&env, gift_id)?;
+
+        // Verify status
+        match gift.status {
+            GiftStatus::Claimed => {}
+            GiftStatus::Created => return Err(Error::GiftNotClaimed),
+            GiftStatus::Unlocked => return Err(Error::AlreadyUnlocked),
+            GiftStatus::Expired => return Err(Error::GiftExpired),
+            GiftStatus::Refunded => return Err(Error::GiftRefunded),
+            GiftStatus::Cancelled => return Err(Error::GiftCancelled),
+        }
+
+        // Only the recipient bound at claim time can unlock
+        if gift.recipient != Some(recipient.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        // Verify Unlock Time
+        let now = env.ledger().timestamp();
+        if now < gift.unlock_timestamp {
+            return Err(Error::UnlockTimeNotReached);
+        }
+
+        if quote_expiry.is_some_and(|expiry| now > expiry) {
+            return Err(Error::QuoteExpired);
+        }
+
+        // Fiat gifts are re-quoted: the recipient receives the token value of
+        // the fiat amount and the sender the excess
+        let settlement = match &gift.denomination {
+            GiftDenomination::Fiat(denomination) => {
+                let rate = match guarded_exchange_rate(
+                    &env,
+                    &denomination.pair,
+                    denomination.max_slippage_bps,
+                )? {
+                    RateStatus::Fresh(rate) => rate,
+                    RateStatus::Tripped(trip) => return Ok(UnlockOutcome::Tripped(trip)),
+                };
+                let (owed, payout) = fiat_payout(&env, &gift, denomination, rate)?;
+                Some((denomination.fiat_amount, rate, owed, payout))
+            }
+            GiftDenomination::Token => None,
+        };
+        let payout = settlement.map_or(gift.amount, |(_, _, _, payout)| payout);
+        let sender_refund = gift.amount - payout;
+
+        // Resolve any payout swap before changing state, so a breaker trip
+        // can be reported without rolling back the pause. The gift's own
+        // tolerance also bounds the swap.
+        let swap = match payout_token {
+            Some(payout_token) => {
+                let max_slippage_bps = match &gift.denomination {
+                    GiftDenomination::Fiat(denomination) => denomination.max_slippage_bps,
+                    GiftDenomination::Token => None,
+                };
+                let (route, router) = get_payout_swap_route(&env, &payout_token)?;
+                let oracle_rate =
+                    match guarded_exchange_rate(&env, &route.currency_pair, max_slippage_bps)? {
+                        RateStatus::Fresh(rate) => rate,
+                        RateStatus::Tripped(trip) => return Ok(UnlockOutcome::Tripped(trip)),
+                    };
+                Some(PayoutSwap {
+                    payout_token,
+                    route,
+                    router,
+                    oracle_rate,
+                    max_slippage_bps,
+                })
+            }
+            None => None,
+        };
+
+        if swap.is_none() && min_out.is_some_and(|min_out| payout < min_out) {
+            return Err(Error::SlippageExceeded);
+        }
+
+        gift.status = GiftStatus::Unlocked;
+        save_gift(&env, gift_id, &gift);
+        extend_instance_ttl(&env);
+
+        // Release escrowed funds to the recipient, swapped if requested
+        let token = get_token_client(&env)?;
+        let paid = match swap {
+            Some(swap) => swap_payout(&env, gift_id, &recipient, swap, payout, min_out)?,
+            None => {
+                token.transfer(&env.current_contract_address(), &recipient, &payout);
+                payout
+            }
+        };
+        if sender_refund > 0 {
+            token.transfer(&env.current_contract_address(), &gift.sender, &sender_refund);
+        }
+
+        if let Some((fiat_amount, rate, owed, payout)) = settlement {
+            if payout < owed {
+                env.events().publish(
+                    (symbol_short!("fiat_cap"),),
+                    FiatSettlementCapped {
+                        gift_id,
+                        owed,
+                        payout,
+                    },
+                );
+            }
+            env.events().publish(
+                (symbol_short!("fiat_set"),),
+                FiatGiftSettled {
+                    gift_id,
+                    fiat_amount,
+                    rate,
+                    payout,
+                    sender_refund,
+                },
+            );
+        }
+
+        env.events().publish(
+            (symbol_short!("unlocked"),),
+            GiftUnlocked {
+                gift_id,
+                recipient,
+                amount: payout,
+                timestamp: now,
+            },
+        );
+
+        Ok(UnlockOutcome::Unlocked(paid))
+    }
+
+    /// Get a gift by id (public view)
+    pub fn get_gift(env: Env, gift_id: u64) -> Result<Gift, Error> {
+        load_gift(&env, gift_id)
+    }
+
+    /// Get seconds remaining until a gift unlocks (0 once unlocked)
+    pub fn get_time_remaining(env: Env, gift_id: u64) -> Result<u64, Error> {
+        let gift = load_gift(&env, gift_id)?;
+        Ok(gift.unlock_timestamp.saturating_sub(env.ledger().timestamp()))
+    }
+
+    /// Whether a claimed gift's unlock time has passed so `unlock_gift` can
+    /// release the funds
+    pub fn can_unlock(env: Env, gift_id: u64) -> Result<bool, Error> {
+        let gift = load_gift(&env, gift_id)?;
+        Ok(gift.status == GiftStatus::Claimed
+            && env.ledger().timestamp() >= gift.unlock_timestamp)
+    }
+
+    /// List gifts created by a sender, oldest first.
+    /// `cursor` is the previous page's `next_cursor` (0 for the first page).
+    pub fn get_gifts_by_sender(
+        env: Env,
+        sender: Address,
+        cursor: u64,
+        limit: u32,
+    ) -> Result<GiftPage, Error> {
+        index_page(&env, GiftIndex::Sender(sender), cursor, limit)
+    }
+
+    /// List unclaimed gifts waiting for a phone hash, oldest first.
+    /// `cursor` is the previous page's `next_cursor` (0 for the first page).
+    pub fn get_gifts_by_phone_hash(
+        env: Env,
+        recipient_phone_hash: String,
+        cursor: u64,
+        limit: u32,
+    ) -> Result<GiftPage, Error> {
+        index_page(&env, GiftIndex::PhoneHash(recipient_phone_hash), cursor, limit)
+    }
+
+    /// Extend the storage TTL of a gift (and the contract instance) so long
+    /// time-locks are not archived before they unlock. Callable by anyone.
+    pub fn extend_gift_ttl(env: Env, gift_id: u64) -> Result<(), Error> {
+        let gift = load_gift(&env, gift_id)?;
+        extend_gift_ttl_internal(&env, gift_id, &gift);
+        extend_instance_ttl(&env);
+        Ok(())
+    }
+
+    /// Mark an unclaimed gift as expired once its claim deadline has passed.
+    /// Callable by anyone so indexers can observe the status change.
+    pub fn expire_gift(env: Env, gift_id: u64) -> Result<(), Error> {
+        let mut gift = load_gift(&env, gift_id)?;
+
+        if gift.status != GiftStatus::Created {
+            return Err(Error::InvalidStatus);
+        }
+        if !gift.is_past_deadline(env.ledger().timestamp()) {
+            return Err(Error::RefundNotAvailable);
+        }
+
+        gift.status = GiftStatus::Expired;
+        save_gift(&env, gift_id, &gift);
+        index_remove_phone_hash(&env, gift_id, &gift.recipient_phone_hash);
+
+        env.events().publish(
+            (symbol_short!("expired"),),
+            GiftExpired {
+                gift_id,
+                claim_deadline: gift.claim_deadline.unwrap_or_default(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Return the escrowed amount of an unclaimed gift to its sender after
+    /// the claim deadline. The protocol fee is not refunded.
+    pub fn refund_gift(env: Env, gift_id: u64) -> Result<(), Error> {
+        let mut gift = load_gift(&env, gift_id)?;
+        gift.sender.require_auth();
+
+        match gift.status {
+            GiftStatus::Expired => {}
+            GiftStatus::Created => {
+                if !gift.is_past_deadline(env.ledger().timestamp()) {
+                    return Err(Error::RefundNotAvailable);
+                }
+            }
+            GiftStatus::Refunded => return Err(Error::GiftRefunded),
+            GiftStatus::Claimed => return Err(Error::AlreadyClaimed),
+            _ => return Err(Error::InvalidStatus),
+        }
+
+        gift.status = GiftStatus::Refunded;
+        save_gift(&env, gift_id, &gift);
+        index_remove_phone_hash(&env, gift_id, &gift.recipient_phone_hash);
+
+        get_token_client(&env)?.transfer(&env.current_contract_address(), &gift.sender, &gift.amount);
+
+        env.events().publish(
+            (symbol_short!("refunded"),),
+            GiftRefunded {
+                gift_id,
+                sender: gift.sender,
+                amount: gift.amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Cancel a gift before it unlocks and return the escrowed amount to the
+    /// sender, less the cancellation fee. Only allowed within the
+    /// cancellation window after creation, whether or not it has been claimed.
+    pub fn cancel_gift(env: Env, gift_id: u64) -> Result<(), Error> {
+        let mut gift = load_gift(&env, gift_id)?;
+        gift.sender.require_auth();
+
+        // A claimed gift has not released funds yet; the sender can still
+        // recover it from a wrong recipient while the window is open
+        match gift.status {
+            GiftStatus::Created | GiftStatus::Claimed => {}
+            GiftStatus::Cancelled => return Err(Error::GiftCancelled),
+            GiftStatus::Refunded => return Err(Error::GiftRefunded),
+            _ => return Err(Error::InvalidStatus),
+        }
+
+        let now = env.ledger().timestamp();
+        let policy = get_cancellation_policy_internal(&env);
+        if now >= gift.unlock_timestamp
+            || now > gift.created_at.saturating_add(policy.window_secs)
+        {
+            return Err(Error::CancellationWindowClosed);
+        }
+
+        let cancellation_fee = fees::calculate_bps_fee(gift.amount, policy.fee_bps);
+        let refunded_amount = gift.amount - cancellation_fee;
+        if cancellation_fee > 0 {
+            let fee_balance = get_fee_balance_internal(&env) + cancellation_fee;
+            env.storage().instance().set(&DataKey::FeeBalance, &fee_balance);
+        }
+
+        gift.status = GiftStatus::Cancelled;
+        save_gift(&env, gift_id, &gift);
+        index_remove_phone_hash(&env, gift_id, &gift.recipient_phone_hash);
+
+        get_token_client(&env)?.transfer(
+            &env.current_contract_address(),
+            &gift.sender,
+            &refunded_amount,
+        );
+
+        env.events().publish(
+            (symbol_short!("cancelled"),),
+            GiftCancelled {
+                gift_id,
+                sender: gift.sender,
+                refunded_amount,
+                cancellation_fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get current cancellation policy (public view)
+    pub fn get_cancellation_policy(env: Env) -> Result<CancellationPolicy, Error> {
+        Ok(get_cancellation_policy_internal(&env))
+    }
+
+    /// Set cancellation window and fee (admin only)
+    pub fn set_cancellation_policy(env: Env, window_secs: u64, fee_bps: u32) -> Result<(), Error> {
+        if fee_bps > 10000 {
+            return Err(Error::InvalidCancellationPolicy);
+        }
+
+        let admin = require_admin_auth(&env)?;
+
+        env.storage().instance().set(
+            &DataKey::CancellationPolicy,
+            &CancellationPolicy { window_secs, fee_bps },
+        );
+
+        env.events().publish(
+            (symbol_short!("cncl_pol"),),
+            CancellationPolicyUpdated {
+                window_secs,
+                fee_bps,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Register an attestation key for `attestor_id` with a validity window
+    /// (admin only). Windows may overlap so keys can be rotated without
+    /// downtime; keys sharing an attestor id count as one attestor.
+    pub fn add_attestation_key(
+        env: Env,
+        attestor_id: u32,
+        public_key: AttestorPublicKey,
+        active_from: u64,
+        expires_at: Option<u64>,
+    ) -> Result<u32, Error> {
+        let _admin = require_admin_auth(&env)?;
+
+        add_attestation_key_internal(
+            &env,
+            AttestationKey {
+                attestor_id,
+                public_key,
+                active_from,
+                expires_at,
+                revoked: false,
+            },
+        )
+    }
+
+    /// Revoke an attestation key immediately (admin only)
+    pub fn revoke_attestation_key(env: Env, key_id: u32) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+
+        let mut keys = get_attestation_keys(&env);
+        let mut key = keys.get(key_id).ok_or(Error::AttestationKeyNotFound)?;
+        key.revoked = true;
+        keys.set(key_id, key);
+        env.storage().instance().set(&DataKey::AttestationKeys, &keys);
+
+        env.events().publish(
+            (symbol_short!("att_rev"),),
+            AttestationKeyRevoked { key_id, admin },
+        );
+
+        Ok(())
+    }
+
+    /// List attestation keys that are valid right now (public view)
+    pub fn get_active_attestation_keys(env: Env) -> Result<Vec<AttestationKeyRecord>, Error> {
+        let now = env.ledger().timestamp();
+        let mut active = Vec::new(&env);
+        for (key_id, key) in get_attestation_keys(&env).iter() {
+            if key.is_valid_at(now) {
+                active.push_back(AttestationKeyRecord { key_id, key });
+            }
+        }
+        Ok(active)
+    }
+
+    /// Get the attestor threshold policy (public view)
+    pub fn get_attestor_policy(env: Env) -> AttestorPolicy {
+        get_attestor_policy_internal(&env)
+    }
+
+    /// Require `threshold` distinct attestors for gifts above
+    /// `high_value_amount` (admin only). Smaller gifts need one signature.
+    pub fn set_attestor_policy(
+        env: Env,
+        threshold: u32,
+        high_value_amount: i128,
+    ) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+
+        // Distinct attestors holding a key that is not revoked or expired
+        let now = env.ledger().timestamp();
+        let mut attestor_ids: Vec<u32> = Vec::new(&env);
+        for key in get_attestation_keys(&env).values().iter() {
+            let live = !key.revoked && key.expires_at.is_none_or(|expires_at| now <= expires_at);
+            if live && !attestor_ids.contains(key.attestor_id) {
+                attestor_ids.push_back(key.attestor_id);
+            }
+        }
+        let registered = attestor_ids.len();
+        if threshold == 0 || threshold > registered || high_value_amount < 0 {
+            return Err(Error::InvalidAttestorPolicy);
+        }
+
+        env.storage().instance().set(
+            &DataKey::AttestorPolicy,
+            &AttestorPolicy {
+                threshold,
+                high_value_amount,
+            },
+        );
+
+        env.events().publish(
+            (symbol_short!("att_pol"),),
+            AttestorPolicyUpdated {
+                threshold,
+                high_value_amount,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get protocol fees currently held for the treasury (public view)
+    pub fn get_fee_balance(env: Env) -> Result<i128, Error> {
+        Ok(get_fee_balance_internal(&env))
+    }
+
+    /// Withdraw accumulated protocol fees to the configured fee treasury
+    /// (admin only)
+    pub fn withdraw_fees(env: Env, amount: i128) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+        let to = get_config_internal(&env)?.fee_treasury;
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let fee_balance = get_fee_balance_internal(&env);
+        if amount > fee_balance {
+            return Err(Error::InsufficientFeeBalance);
+        }
+
+        let remaining_balance = fee_balance - amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeBalance, &remaining_balance);
+
+        get_token_client(&env)?.transfer(&env.current_contract_address(), &to, &amount);
+
+        env.events().publish(
+            (symbol_short!("fee_wd"),),
+            FeesWithdrawn {
+                to,
+                amount,
+                remaining_balance,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get current oracle configuration (public view)
+    pub fn get_oracle_status(env: Env) -> Result<OracleConfig, Error> {
+        get_oracle_config(&env)
+    }
+
+    /// Replace the oracle sources with a single price feed (admin only)
+    pub fn set_oracle_address(env: Env, new_oracle_address: Address) -> Result<(), Error> {
+        let _admin = require_admin_auth(&env)?;
+
+        let mut oracle_config = get_oracle_config(&env)?;
+        let old_address = oracle_config
+            .oracle_sources
+            .first()
+            .unwrap_or(new_oracle_address.clone());
+        oracle_config.oracle_sources = Vec::from_array(&env, [new_oracle_address.clone()]);
+        oracle_config.min_quorum = 1;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::OracleConfig, &oracle_config);
+
+        let mut config = get_config_internal(&env)?;
+        config.price_oracle = new_oracle_address.clone();
+        env.storage().instance().set(&DataKey::Config, &config);
+        clear_price_caches(&env);
+
+        env.events().publish(
+            (symbol_short!("oracle_ad"),),
+            OracleAddressUpdated {
+                old_address,
+                new_address: new_oracle_address,
+            },
+        );
+
+        Ok(())
     }
 
-    pub fn create_gift(
+    /// Set the oracle sources aggregated by `check_exchange_rate`, the
+    /// number of valid responses required, and the maximum spread allowed
+    /// between them (admin only)
+    pub fn set_oracle_sources(
         env: Env,
-        sender: Address,
-        amount: i128,
-        unlock_timestamp: u64,
-        recipient_phone_hash: String,
-    ) -> u64 {
-        sender.require_auth();
+        sources: Vec<Address>,
+        min_quorum: u32,
+        max_deviation_bps: u32,
+    ) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
 
-        // Check amount limits
-        if amount < constants::MIN_GIFT_AMOUNT || amount > constants::MAX_GIFT_AMOUNT {
-            panic!("Invalid amount");
-        }
+        oracle::validate_oracle_sources(&sources, min_quorum, max_deviation_bps)
+            .map_err(|_| Error::InvalidOracleConfig)?;
 
-        let gift_id: u64 = env
-            .storage()
+        let mut oracle_config = get_oracle_config(&env)?;
+        oracle_config.oracle_sources = sources.clone();
+        oracle_config.min_quorum = min_quorum;
+        oracle_config.max_deviation_bps = max_deviation_bps;
+
+        env.storage()
             .instance()
-            .get(&DataKey::NextGiftId)
-            .unwrap_or(1);
+            .set(&DataKey::OracleConfig, &oracle_config);
+        clear_price_caches(&env);
 
-        let gift = Gift {
-            sender,
-            recipient: None,
-            amount,
-            unlock_timestamp,
-            recipient_phone_hash,
-            status: GiftStatus::Created,
-        };
+        env.events().publish(
+            (symbol_short!("oracle_sr"),),
+            OracleSourcesUpdated {
+                sources,
+                min_quorum,
+                max_deviation_bps,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Set maximum oracle data age (admin only)
+    pub fn set_max_oracle_age(env: Env, max_age: u64) -> Result<(), Error> {
+        let _admin = require_admin_auth(&env)?;
+
+        let mut oracle_config = get_oracle_config(&env)?;
+        oracle::validate_max_age(max_age, &oracle_config).map_err(|_| Error::InvalidOracleAge)?;
+        oracle_config.max_oracle_age = max_age;
 
-        env.storage().instance().set(&DataKey::Gift(gift_id), &gift);
         env.storage()
             .instance()
-            .set(&DataKey::NextGiftId, &(gift_id + 1));
+            .set(&DataKey::OracleConfig, &oracle_config);
 
-        gift_id
+        Ok(())
     }
 
-    pub fn claim_gift(
-        env: Env,
-        claimant: Address,
-        gift_id: u64,
-        verification_proof: BytesN<64>,
-    ) -> Result<(), Error> {
-        claimant.require_auth();
+    /// Set the range max oracle ages must fall within (admin only). The
+    /// current default and every pair override must already be in range.
+    pub fn set_oracle_age_bounds(env: Env, min_age: u64, max_age: u64) -> Result<(), Error> {
+        let _admin = require_admin_auth(&env)?;
 
-        let key = DataKey::Gift(gift_id);
-        if !env.storage().instance().has(&key) {
-            return Err(Error::GiftNotFound);
+        if min_age == 0 || min_age > max_age {
+            return Err(Error::InvalidOracleAge);
         }
 
-        let mut gift: Gift = env.storage().instance().get(&key).unwrap();
-
-        // Verify status
-        if gift.status != GiftStatus::Created {
-            if gift.status == GiftStatus::Claimed {
-                return Err(Error::AlreadyClaimed);
+        let mut oracle_config = get_oracle_config(&env)?;
+        oracle_config.min_age_bound = min_age;
+        oracle_config.max_age_bound = max_age;
+
+        oracle::validate_max_age(oracle_config.max_oracle_age, &oracle_config)
+            .map_err(|_| Error::InvalidOracleAge)?;
+        for pair_config in get_currency_pairs(&env).values().iter() {
+            if let Some(pair_age) = pair_config.max_oracle_age {
+                oracle::validate_max_age(pair_age, &oracle_config)
+                    .map_err(|_| Error::InvalidOracleAge)?;
             }
-            return Err(Error::InvalidStatus);
-        }
-        
-        // Verify Unlock Time
-        if env.ledger().timestamp() < gift.unlock_timestamp {
-             return Err(Error::NotUnlocked);
         }
 
-        // Verify Oracle Proof
-        let oracle_pk: BytesN<32> = env
-            .storage()
+        env.storage()
             .instance()
-            .get(&DataKey::Oracle)
-            .expect("Contract not initialized");
+            .set(&DataKey::OracleConfig, &oracle_config);
 
-        // Construct payload: claimant XDR + recipient_phone_hash XDR
-        let mut payload = Bytes::new(&env);
-        payload.append(&claimant.clone().to_xdr(&env));
-        payload.append(&gift.recipient_phone_hash.clone().to_xdr(&env));
+        Ok(())
+    }
 
-        // Verify signature
-        env.crypto()
-            .ed25519_verify(&oracle_pk, &payload, &verification_proof);
+    /// Register or update a supported currency pair (admin only)
+    pub fn set_currency_pair(
+        env: Env,
+        pair: String,
+        asset: Asset,
+        max_oracle_age: Option<u64>,
+    ) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
 
-        // Update Gift
-        gift.recipient = Some(claimant.clone());
-        gift.status = GiftStatus::Claimed;
+        if let Some(pair_age) = max_oracle_age {
+            oracle::validate_max_age(pair_age, &get_oracle_config(&env)?)
+                .map_err(|_| Error::InvalidOracleAge)?;
+        }
 
-        env.storage().instance().set(&key, &gift);
+        // Keep the pair's slippage limit across updates
+        let mut pairs = get_currency_pairs(&env);
+        let max_slippage_bps = pairs
+            .get(pair.clone())
+            .and_then(|pair_config| pair_config.max_slippage_bps);
+        pairs.set(
+            pair.clone(),
+            PairConfig {
+                asset: asset.clone(),
+                max_oracle_age,
+                max_slippage_bps,
+            },
+        );
+        env.storage().instance().set(&DataKey::CurrencyPairs, &pairs);
+        env.storage()
+            .temporary()
+            .remove(&DataKey::PriceCache(pair.clone()));
 
-        // Emit event
         env.events().publish(
-            (symbol_short!("claimed"),),
-            (gift_id, claimant, env.ledger().timestamp()),
+            (symbol_short!("pair_upd"),),
+            CurrencyPairUpdated {
+                pair,
+                asset,
+                max_oracle_age,
+                admin,
+            },
         );
 
-    /// Initialize contract with admin and oracle address
-    pub fn initialize(env: Env, admin: Address, oracle_address: Address) -> Result<(), Error> {
-        if env.storage().persistent().has(&symbol_short!("admin")) {
-            return Err(Error::Unauthorized);
-        }
+        Ok(())
+    }
 
-        env.storage()
-            .persistent()
-            .set(&symbol_short!("admin"), &admin);
+    /// Set or clear a currency pair's slippage limit (admin only). The
+    /// effective limit is the tighter of this and the global limit.
+    pub fn set_pair_slippage(
+        env: Env,
+        pair: String,
+        max_slippage_bps: Option<u32>,
+    ) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
 
-        let oracle_config = oracle::default_oracle_config(oracle_address);
-        env.storage()
-            .persistent()
-            .set(&symbol_short!("oracle"), &oracle_config);
+        if let Some(bps) = max_slippage_bps {
+            slippage::validate_slippage_bounds(bps).map_err(|_| Error::InvalidSlippageConfig)?;
+        }
 
-        let slippage_config = slippage::default_slippage_config(admin.clone());
-        env.storage()
-            .persistent()
-            .set(&symbol_short!("slippage"), &slippage_config);
+        let mut pairs = get_currency_pairs(&env);
+        let mut pair_config = pairs.get(pair.clone()).ok_or(Error::UnsupportedCurrencyPair)?;
+        pair_config.max_slippage_bps = max_slippage_bps;
+        pairs.set(pair.clone(), pair_config);
+        env.storage().instance().set(&DataKey::CurrencyPairs, &pairs);
+
+        env.events().publish(
+            (symbol_short!("pair_slip"),),
+            PairSlippageUpdated {
+                pair,
+                max_slippage_bps,
+                admin,
+            },
+        );
 
         Ok(())
     }
 
-    /// Get current oracle configuration (public view)
-    pub fn get_oracle_status(env: Env) -> Result<OracleConfig, Error> {
-        env.storage()
-            .persistent()
-            .get::<_, OracleConfig>(&symbol_short!("oracle"))
-            .ok_or(Error::OracleUnavailable)
+    /// Set the router used to swap payouts into other tokens (admin only)
+    pub fn set_swap_router(env: Env, router: Address) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+
+        let old_router: Option<Address> = env.storage().instance().get(&DataKey::SwapRouter);
+        env.storage().instance().set(&DataKey::SwapRouter, &router);
+
+        env.events().publish(
+            (symbol_short!("router"),),
+            SwapRouterUpdated {
+                old_router,
+                new_router: router,
+                admin,
+            },
+        );
+
+        Ok(())
     }
 
-    /// Set oracle address (admin only)
-    pub fn set_oracle_address(env: Env, new_oracle_address: Address) -> Result<(), Error> {
-        let _admin = require_admin_auth(&env)?;
+    /// Offer `payout_token` as a payout option (admin only). `path` runs
+    /// from the gift token to the payout token, and `currency_pair` must be a
+    /// registered pair quoting the payout token per gift token.
+    pub fn set_payout_route(
+        env: Env,
+        payout_token: Address,
+        path: Vec<Address>,
+        currency_pair: String,
+    ) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
 
-        let mut oracle_config = get_oracle_config(&env)?;
-        let old_address = oracle_config.oracle_address.clone();
-        oracle_config.oracle_address = new_oracle_address.clone();
+        let gift_token = get_config_internal(&env)?.token;
+        if !swap::validate_route(&path, &gift_token, &payout_token) {
+            return Err(Error::InvalidPayoutRoute);
+        }
+        get_pair_config(&env, &currency_pair)?;
 
-        env.storage()
-            .persistent()
-            .set(&symbol_short!("oracle"), &oracle_config);
+        let route = PayoutRoute {
+            path,
+            currency_pair,
+        };
+        let mut routes = get_payout_routes(&env);
+        routes.set(payout_token.clone(), route.clone());
+        env.storage().instance().set(&DataKey::PayoutRoutes, &routes);
 
         env.events().publish(
-            (symbol_short!("oracle_ad"),),
-            OracleAddressUpdated {
-                old_address,
-                new_address: new_oracle_address,
+            (symbol_short!("route_upd"),),
+            PayoutRouteUpdated {
+                payout_token,
+                route,
+                admin,
             },
         );
 
         Ok(())
     }
 
-    /// Set maximum oracle data age (admin only)
-    pub fn set_max_oracle_age(env: Env, max_age: u64) -> Result<(), Error> {
-        let _admin = require_admin_auth(&env)?;
+    /// Stop offering `payout_token` as a payout option (admin only)
+    pub fn remove_payout_route(env: Env, payout_token: Address) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
 
-        let mut oracle_config = get_oracle_config(&env)?;
-        oracle_config.max_oracle_age = max_age;
+        let mut routes = get_payout_routes(&env);
+        if !routes.contains_key(payout_token.clone()) {
+            return Err(Error::UnsupportedPayoutToken);
+        }
+        routes.remove(payout_token.clone());
+        env.storage().instance().set(&DataKey::PayoutRoutes, &routes);
+
+        env.events().publish(
+            (symbol_short!("route_rm"),),
+            PayoutRouteRemoved {
+                payout_token,
+                admin,
+            },
+        );
 
+        Ok(())
+    }
+
+    /// List the payout routes, keyed by payout token
+    pub fn get_payout_routes(env: Env) -> Map<Address, PayoutRoute> {
+        get_payout_routes(&env)
+    }
+
+    /// Remove a currency pair from the registry (admin only)
+    pub fn remove_currency_pair(env: Env, pair: String) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+
+        let mut pairs = get_currency_pairs(&env);
+        if !pairs.contains_key(pair.clone()) {
+            return Err(Error::UnsupportedCurrencyPair);
+        }
+        pairs.remove(pair.clone());
+        env.storage().instance().set(&DataKey::CurrencyPairs, &pairs);
         env.storage()
-            .persistent()
-            .set(&symbol_short!("oracle"), &oracle_config);
+            .temporary()
+            .remove(&DataKey::PriceCache(pair.clone()));
+
+        env.events().publish(
+            (symbol_short!("pair_rm"),),
+            CurrencyPairRemoved { pair, admin },
+        );
 
         Ok(())
     }
 
+    /// Get the configuration of a supported currency pair
+    pub fn get_currency_pair(env: Env, pair: String) -> Result<PairConfig, Error> {
+        get_pair_config(&env, &pair)
+    }
+
+    /// List supported currency pairs
+    pub fn get_supported_pairs(env: Env) -> Vec<String> {
+        get_currency_pairs(&env).keys()
+    }
+
     /// Pause oracle checks (emergency admin function)
     pub fn pause_oracle_checks(env: Env) -> Result<(), Error> {
         let _admin = require_admin_auth(&env)?;
@@ -246,13 +1910,14 @@ impl TimeLockContract {
         oracle_config.is_paused = true;
 
         env.storage()
-            .persistent()
-            .set(&symbol_short!("oracle"), &oracle_config);
+            .instance()
+            .set(&DataKey::OracleConfig, &oracle_config);
 
         Ok(())
     }
 
-    /// Resume oracle checks (admin function)
+    /// Resume oracle checks (admin function). A pending circuit breaker
+    /// trip is cleared and its new rate accepted as the pair's latest rate.
     pub fn resume_oracle_checks(env: Env) -> Result<(), Error> {
         let _admin = require_admin_auth(&env)?;
 
@@ -260,12 +1925,62 @@ impl TimeLockContract {
         oracle_config.is_paused = false;
 
         env.storage()
-            .persistent()
-            .set(&symbol_short!("oracle"), &oracle_config);
+            .instance()
+            .set(&DataKey::OracleConfig, &oracle_config);
+
+        if let Some(trip) = env
+            .storage()
+            .instance()
+            .get::<_, CircuitBreakerTrip>(&DataKey::CircuitBreakerTrip)
+        {
+            record_price(
+                &env,
+                &trip.pair,
+                PricePoint {
+                    rate: trip.new_rate,
+                    timestamp: trip.timestamp,
+                },
+            );
+            env.storage().instance().remove(&DataKey::CircuitBreakerTrip);
+        }
+
+        Ok(())
+    }
+
+    /// Set the rate jump in bps that trips the circuit breaker; 0 disables
+    /// it (admin only)
+    pub fn set_circuit_breaker(env: Env, threshold_bps: u32) -> Result<(), Error> {
+        let admin = require_admin_auth(&env)?;
+
+        if threshold_bps > 10000 {
+            return Err(Error::InvalidOracleConfig);
+        }
+
+        let mut oracle_config = get_oracle_config(&env)?;
+        let old_threshold_bps = oracle_config.circuit_breaker_bps;
+        oracle_config.circuit_breaker_bps = threshold_bps;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::OracleConfig, &oracle_config);
+
+        env.events().publish(
+            (symbol_short!("cb_upd"),),
+            CircuitBreakerUpdated {
+                old_threshold_bps,
+                new_threshold_bps: threshold_bps,
+                admin,
+            },
+        );
 
         Ok(())
     }
 
+    /// Get the circuit breaker trip awaiting admin resume, if any
+    pub fn get_circuit_breaker_trip(env: Env) -> Option<CircuitBreakerTrip> {
+        env.storage().instance().get(&DataKey::CircuitBreakerTrip)
+    }
+
     /// Set maximum slippage (admin only)
     pub fn set_max_slippage(env: Env, slippage_bps: u32) -> Result<(), Error> {
         slippage::validate_slippage_bounds(slippage_bps)
@@ -278,8 +1993,8 @@ impl TimeLockContract {
         slippage_config.max_slippage_bps = slippage_bps;
 
         env.storage()
-            .persistent()
-            .set(&symbol_short!("slippage"), &slippage_config);
+            .instance()
+            .set(&DataKey::SlippageConfig, &slippage_config);
 
         env.events().publish(
             (symbol_short!("slip_upd"),),
@@ -293,79 +2008,229 @@ impl TimeLockContract {
         Ok(())
     }
 
-    /// Get current slippage configuration
-    pub fn get_slippage_config(env: Env) -> Result<SlippageConfig, Error> {
-        get_slippage_config_internal(&env)
-    }
+    /// Scale the slippage limit with each pair's realized volatility: the
+    /// limit becomes the base plus `volatility_multiplier_bps` of the
+    /// volatility, clamped to `[min_bps, max_bps]`. A zero multiplier keeps
+    /// the static limit (admin only).
+    pub fn set_dynamic_slippage(
+        env: Env,
+        volatility_multiplier_bps: u32,
+        min_bps: u32,
+        max_bps: u32,
+    ) -> Result<(), Error> {
+        slippage::validate_dynamic_bounds(min_bps, max_bps)
+            .map_err(|_| Error::InvalidSlippageConfig)?;
 
-    /// Query current exchange rate from cache or oracle
-    /// Returns rate with precision factor (1000000 = 1.0)
-    pub fn check_exchange_rate(env: Env, _currency_pair: String) -> Result<i128, Error> {
-        let oracle_config = get_oracle_config(&env)?;
+        let admin = require_admin_auth(&env)?;
 
-        if oracle_config.is_paused {
-            return Err(Error::OraclePaused);
-        }
+        let mut slippage_config = get_slippage_config_internal(&env)?;
+        slippage_config.volatility_multiplier_bps = volatility_multiplier_bps;
+        slippage_config.min_dynamic_bps = min_bps;
+        slippage_config.max_dynamic_bps = max_bps;
 
-        // Try to get cached price first
-        if let Some(cached) = env
-            .storage()
-            .temporary()
-            .get::<_, PriceCache>(&symbol_short!("price"))
-        {
-            let current_ledger = env.ledger().timestamp();
-            if current_ledger.saturating_sub(cached.timestamp) < oracle_config.max_oracle_age {
-                return Ok(cached.rate);
-            }
+        env.storage()
+            .instance()
+            .set(&DataKey::SlippageConfig, &slippage_config);
+
+        env.events().publish(
+            (symbol_short!("slip_dyn"),),
+            DynamicSlippageUpdated {
+                volatility_multiplier_bps,
+                min_dynamic_bps: min_bps,
+                max_dynamic_bps: max_bps,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Bound favourable slippage to `max_bps`, or leave upside moves
+    /// unbounded with `None` (admin only)
+    pub fn set_favourable_slippage(env: Env, max_bps: Option<u32>) -> Result<(), Error> {
+        if let Some(bps) = max_bps {
+            slippage::validate_slippage_bounds(bps).map_err(|_| Error::InvalidSlippageConfig)?;
         }
 
-        // Placeholder for actual oracle call
-        let oracle_rate: i128 = 1_000_000; // 1.0 USDC/NGN (placeholder)
-        let current_timestamp = env.ledger().timestamp();
+        let admin = require_admin_auth(&env)?;
 
-        // Validate rate bounds
-        oracle::validate_rate_bounds(oracle_rate).map_err(|_| Error::InvalidExchangeRate)?;
+        let mut slippage_config = get_slippage_config_internal(&env)?;
+        let old_limit = slippage_config.max_favourable_bps;
+        slippage_config.max_favourable_bps = max_bps;
 
-        // Cache the rate
-        env.storage().temporary().set(
-            &symbol_short!("price"),
-            &PriceCache {
-                rate: oracle_rate,
-                timestamp: current_timestamp,
-            },
-        );
+        env.storage()
+            .instance()
+            .set(&DataKey::SlippageConfig, &slippage_config);
 
         env.events().publish(
-            (symbol_short!("price_q"),),
-            OracleRateQueried {
-                timestamp: current_timestamp,
-                rate: oracle_rate,
-                source: String::from_str(&env, "oracle"),
+            (symbol_short!("slip_fav"),),
+            FavourableSlippageUpdated {
+                old_limit,
+                new_limit: max_bps,
+                admin,
             },
         );
 
-        Ok(oracle_rate)
+        Ok(())
+    }
+
+    /// Compare execution rates against the TWAP over `window_secs` instead
+    /// of the spot rate; 0 restores spot comparison (admin only)
+    pub fn set_slippage_twap_window(env: Env, window_secs: u64) -> Result<(), Error> {
+        let _admin = require_admin_auth(&env)?;
+
+        let mut slippage_config = get_slippage_config_internal(&env)?;
+        slippage_config.twap_window_secs = window_secs;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::SlippageConfig, &slippage_config);
+
+        Ok(())
+    }
+
+    /// Get the slippage configuration and the limit currently in effect for
+    /// a currency pair, with the volatility and pair settings it derives from
+    pub fn get_slippage_config(env: Env, currency_pair: String) -> Result<SlippageStatus, Error> {
+        get_slippage_status(&env, &currency_pair)
+    }
+
+    /// Query current exchange rate from cache or oracle
+    /// Returns rate with precision factor (1000000 = 1.0)
+    ///
+    /// If the new rate trips the circuit breaker, oracle checks are paused
+    /// and `RateStatus::Tripped` is returned so the pause is persisted;
+    /// every later call fails with `OraclePaused` until an admin resumes.
+    pub fn check_exchange_rate(env: Env, currency_pair: String) -> Result<RateStatus, Error> {
+        query_exchange_rate(&env, &currency_pair)
+    }
+
+    /// Quote `amount` of a pair's base asset in its quote asset: the gross
+    /// output, protocol fee and the minimum output within the slippage limit.
+    /// This is a sender-side quote; use `get_gift_quote` for a payout.
+    /// The quote expires after `QUOTE_VALIDITY_SECS`.
+    pub fn get_quote(env: Env, amount: i128, currency_pair: String) -> Result<QuoteOutcome, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let rate = match guarded_exchange_rate(&env, &currency_pair, None)? {
+            RateStatus::Fresh(rate) => rate,
+            RateStatus::Tripped(trip) => return Ok(QuoteOutcome::Tripped(trip)),
+        };
+        let (max_slippage_bps, _) = pair_slippage_limit(&env, &currency_pair, None)?;
+        Ok(QuoteOutcome::Quoted(slippage::build_quote(
+            amount,
+            rate,
+            max_slippage_bps,
+            env.ledger().timestamp() + constants::QUOTE_VALIDITY_SECS,
+        )))
+    }
+
+    /// Quote what `unlock_gift` would pay for a gift now, in the token the
+    /// recipient receives: the gift token, or `payout_token` if swapped. No
+    /// fee is deducted. `min_output` allows for the slippage limit and
+    /// `expires_at` for `QUOTE_VALIDITY_SECS`; both can be passed straight
+    /// to `unlock_gift` as `min_out` and `quote_expiry`.
+    pub fn get_gift_quote(
+        env: Env,
+        gift_id: u64,
+        payout_token: Option<Address>,
+    ) -> Result<QuoteOutcome, Error> {
+        let gift = load_gift(&env, gift_id)?;
+        let expires_at = env.ledger().timestamp() + constants::QUOTE_VALIDITY_SECS;
+
+        // Gift-token payout and the tolerance it settles within
+        let (payout, gift_slippage_bps, payout_bps) = match &gift.denomination {
+            GiftDenomination::Fiat(denomination) => {
+                let rate = match guarded_exchange_rate(
+                    &env,
+                    &denomination.pair,
+                    denomination.max_slippage_bps,
+                )? {
+                    RateStatus::Fresh(rate) => rate,
+                    RateStatus::Tripped(trip) => return Ok(QuoteOutcome::Tripped(trip)),
+                };
+                let (_, payout) = fiat_payout(&env, &gift, denomination, rate)?;
+                let (bps, _) =
+                    pair_slippage_limit(&env, &denomination.pair, denomination.max_slippage_bps)?;
+                (payout, denomination.max_slippage_bps, bps)
+            }
+            GiftDenomination::Token => (gift.amount, None, 0),
+        };
+
+        let quote = match payout_token {
+            Some(payout_token) => {
+                let (route, _) = get_payout_swap_route(&env, &payout_token)?;
+                let rate =
+                    match guarded_exchange_rate(&env, &route.currency_pair, gift_slippage_bps)? {
+                        RateStatus::Fresh(rate) => rate,
+                        RateStatus::Tripped(trip) => return Ok(QuoteOutcome::Tripped(trip)),
+                    };
+                let (bps, _) = pair_slippage_limit(&env, &route.currency_pair, gift_slippage_bps)?;
+                slippage::build_payout_quote(payout, rate, bps, expires_at)
+            }
+            None => slippage::build_payout_quote(
+                payout,
+                10i128.pow(oracle::RATE_DECIMALS),
+                payout_bps,
+                expires_at,
+            ),
+        };
+        Ok(QuoteOutcome::Quoted(quote))
     }
 
     /// Validate slippage before transaction
-    /// Returns error if slippage exceeds threshold
+    /// Returns error if an unfavourable move exceeds the threshold, or a
+    /// favourable move exceeds the favourable limit when one is set
     pub fn validate_slippage(env: Env, oracle_rate: i128, actual_rate: i128) -> Result<(), Error> {
         let slippage_config = get_slippage_config_internal(&env)?;
-        let rate_diff = slippage::calculate_rate_difference(oracle_rate, actual_rate);
+        check_slippage(
+            &env,
+            oracle_rate,
+            actual_rate,
+            (slippage_config.max_slippage_bps, SlippageLimit::Global),
+        )
+    }
+
+    /// Validate an execution rate for a pair against the configured
+    /// reference: the TWAP when a TWAP window is set, otherwise the spot rate.
+    /// Returns the reference rate, or the trip if the spot query tripped the
+    /// circuit breaker (nothing is validated then).
+    pub fn validate_pair_slippage(
+        env: Env,
+        currency_pair: String,
+        actual_rate: i128,
+    ) -> Result<RateStatus, Error> {
+        let slippage_config = get_slippage_config_internal(&env)?;
+        let reference_rate = if slippage_config.twap_window_secs > 0 {
+            get_twap_internal(&env, &currency_pair, slippage_config.twap_window_secs)?
+        } else {
+            match query_exchange_rate(&env, &currency_pair)? {
+                RateStatus::Fresh(rate) => rate,
+                tripped => return Ok(tripped),
+            }
+        };
+        let limit = pair_slippage_limit(&env, &currency_pair, None)?;
+        check_slippage(&env, reference_rate, actual_rate, limit)?;
+        Ok(RateStatus::Fresh(reference_rate))
+    }
 
-        if rate_diff.abs() > slippage_config.max_slippage_bps as i128 {
-            env.events().publish(
-                (symbol_short!("slip_f"),),
-                SlippageCheckFailed {
-                    expected_rate: oracle_rate,
-                    actual_rate,
-                    threshold: slippage_config.max_slippage_bps,
-                },
-            );
-            return Err(Error::SlippageExceeded);
-        }
+    /// Time-weighted average rate of a pair over the last `window_secs`,
+    /// computed from the recorded price history
+    pub fn get_twap(env: Env, currency_pair: String, window_secs: u64) -> Result<i128, Error> {
+        get_twap_internal(&env, &currency_pair, window_secs)
+    }
 
-        Ok(())
+    /// Recorded rate of a pair in effect at `timestamp` (audit lookup)
+    pub fn get_rate_at(env: Env, currency_pair: String, timestamp: u64) -> Result<PricePoint, Error> {
+        get_pair_config(&env, &currency_pair)?;
+        oracle::rate_at(&load_price_history(&env, &currency_pair), timestamp)
+            .ok_or(Error::NoPriceHistory)
     }
-}
 
+    /// Get the recorded rate history of a pair, oldest first
+    pub fn get_price_history(env: Env, currency_pair: String) -> Result<Vec<PricePoint>, Error> {
+        get_pair_config(&env, &currency_pair)?;
+        Ok(load_price_history(&env, &currency_pair))
+    }
+}
@@ -1,4 +1,33 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contractclient, contracttype, Address, Env, Map, String, Symbol, Vec};
+
+use crate::errors::Error;
+
+/// Decimal places of every rate the contract stores or returns (1_000_000 = 1.0)
+pub const RATE_DECIMALS: u32 = 6;
+
+/// SEP-40 asset identifier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// SEP-40 price record as returned by `lastprice`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeedPrice {
+    pub price: i128,             // Price scaled by the feed's `decimals()`
+    pub timestamp: u64,          // Timestamp the feed recorded the price at
+}
+
+/// Read interface of a SEP-40 price feed contract
+#[contractclient(name = "PriceFeedClient")]
+pub trait PriceFeed {
+    fn lastprice(env: Env, asset: Asset) -> Option<FeedPrice>;
+    fn decimals(env: Env) -> u32;
+    fn resolution(env: Env) -> u32;
+}
 
 /// Oracle price data structure
 #[contracttype]
@@ -13,20 +42,197 @@ pub struct PriceData {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct OracleConfig {
-    pub oracle_address: Address,        // Oracle contract address
-    pub max_oracle_age: u64,            // Max age of oracle data in ledger entries
+    pub oracle_sources: Vec<Address>,   // SEP-40 price feed contracts queried for every rate
+    pub min_quorum: u32,                // Valid responses required to produce a rate
+    pub max_deviation_bps: u32,         // Max spread between the lowest and highest valid rates
+    pub max_oracle_age: u64,            // Default max age of oracle data in seconds
+    pub min_age_bound: u64,             // Lowest max age the admin may configure
+    pub max_age_bound: u64,             // Highest max age the admin may configure
+    pub circuit_breaker_bps: u32,       // Rate jump that auto-pauses oracle checks (0 = disabled)
     pub is_paused: bool,                // Whether oracle checks are paused
 }
 
+/// Why the circuit breaker paused oracle checks
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerTrip {
+    pub pair: String,
+    pub previous_rate: i128,            // Last recorded rate for the pair
+    pub new_rate: i128,                 // Aggregated rate that tripped the breaker
+    pub deviation_bps: i128,            // Absolute change between the two rates
+    pub threshold_bps: u32,             // Breaker threshold at the time of the trip
+    pub timestamp: u64,                 // Feed timestamp of the new rate
+}
+
+/// Outcome of a rate query. A query that trips the circuit breaker reports
+/// the trip as a successful result so the pause it sets is persisted.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RateStatus {
+    Fresh(i128),                  // Current aggregated rate
+    Tripped(CircuitBreakerTrip),  // Breaker tripped; no rate is usable until resumed
+}
+
+/// One aggregated rate observation kept in a pair's price history
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PricePoint {
+    pub rate: i128,                     // Normalized to `RATE_DECIMALS`
+    pub timestamp: u64,                 // Feed timestamp of the observation
+}
+
+/// A validated, normalized price from one oracle source
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourcePrice {
+    pub rate: i128,                     // Normalized to `RATE_DECIMALS`
+    pub timestamp: u64,                 // Feed timestamp of the price
+    pub valid_until: u64,               // Feed timestamp plus the feed's resolution
+}
+
+/// Supported currency pair and the feed asset that prices it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PairConfig {
+    pub asset: Asset,                   // Asset queried from the price feed
+    pub max_oracle_age: Option<u64>,    // Max age for this pair (None = oracle default)
+    pub max_slippage_bps: Option<u32>,  // Slippage limit for this pair (None = global limit)
+}
+
+impl PairConfig {
+    /// Max data age for this pair, falling back to the oracle default
+    pub fn max_age(&self, oracle_config: &OracleConfig) -> u64 {
+        self.max_oracle_age.unwrap_or(oracle_config.max_oracle_age)
+    }
+}
+
+/// Currency pair registered at initialization
+pub const DEFAULT_CURRENCY_PAIR: &str = "USDC/NGN";
+
+/// Pair registry seeded at initialization: USDC/NGN priced by the NGN feed asset
+pub fn default_pairs(env: &Env) -> Map<String, PairConfig> {
+    let mut pairs = Map::new(env);
+    pairs.set(
+        String::from_str(env, DEFAULT_CURRENCY_PAIR),
+        PairConfig {
+            asset: Asset::Other(Symbol::new(env, "NGN")),
+            max_oracle_age: None,
+            max_slippage_bps: None,
+        },
+    );
+    pairs
+}
+
 /// Default oracle configuration
-pub fn default_oracle_config(oracle_address: Address) -> OracleConfig {
+pub fn default_oracle_config(env: &Env, oracle_address: Address) -> OracleConfig {
     OracleConfig {
-        oracle_address,
-        max_oracle_age: 300, // 5 minutes (300 ledger entries)
+        oracle_sources: Vec::from_array(env, [oracle_address]),
+        min_quorum: 1,
+        max_deviation_bps: 500, // 5% spread between sources
+        max_oracle_age: 300, // 5 minutes
+        min_age_bound: 30,
+        max_age_bound: 3600,
+        circuit_breaker_bps: 1000, // Pause on a 10% jump between consecutive rates
         is_paused: false,
     }
 }
 
+/// Validate oracle source settings
+pub fn validate_oracle_sources(
+    sources: &Vec<Address>,
+    min_quorum: u32,
+    max_deviation_bps: u32,
+) -> Result<(), &'static str> {
+    if min_quorum == 0 || min_quorum > sources.len() {
+        return Err("Quorum must be between 1 and the number of sources");
+    }
+    if max_deviation_bps > 10000 {
+        return Err("Deviation exceeds maximum bounds (10000 bps)");
+    }
+    for (i, source) in sources.iter().enumerate() {
+        if sources.first_index_of(&source) != Some(i as u32) {
+            return Err("Duplicate oracle source");
+        }
+    }
+    Ok(())
+}
+
+/// Query one SEP-40 feed and normalize its price for `asset`.
+///
+/// Unreachable feeds and missing prices map to `OracleUnavailable`, and
+/// prices that cannot be normalized or are not positive to
+/// `InvalidExchangeRate`. Freshness is left to the caller.
+pub fn fetch_source_price(
+    env: &Env,
+    source: &Address,
+    asset: &Asset,
+) -> Result<SourcePrice, Error> {
+    let feed = PriceFeedClient::new(env, source);
+    let price = match feed.try_lastprice(asset) {
+        Ok(Ok(Some(price))) => price,
+        _ => return Err(Error::OracleUnavailable),
+    };
+    let decimals = match feed.try_decimals() {
+        Ok(Ok(decimals)) => decimals,
+        _ => return Err(Error::OracleUnavailable),
+    };
+    let resolution = match feed.try_resolution() {
+        Ok(Ok(resolution)) => resolution,
+        _ => return Err(Error::OracleUnavailable),
+    };
+
+    let rate = normalize_price(price.price, decimals).ok_or(Error::InvalidExchangeRate)?;
+    validate_rate_bounds(rate).map_err(|_| Error::InvalidExchangeRate)?;
+
+    Ok(SourcePrice {
+        rate,
+        timestamp: price.timestamp,
+        valid_until: price.timestamp.saturating_add(resolution as u64),
+    })
+}
+
+/// Convert a fiat amount to the gift token at `rate` (fiat units per token,
+/// with `RATE_DECIMALS` precision). Returns `None` for a non-positive rate
+/// or on overflow.
+pub fn fiat_to_token(fiat_amount: i128, rate: i128, round_up: bool) -> Option<i128> {
+    if rate <= 0 {
+        return None;
+    }
+    let numerator = fiat_amount.checked_mul(10i128.checked_pow(RATE_DECIMALS)?)?;
+    if round_up {
+        Some(numerator.checked_add(rate - 1)? / rate)
+    } else {
+        Some(numerator / rate)
+    }
+}
+
+/// Insert a rate keeping the vector in ascending order
+pub fn insert_sorted(rates: &mut Vec<i128>, rate: i128) {
+    let position = rates.iter().position(|r| r > rate).unwrap_or(rates.len() as usize);
+    rates.insert(position as u32, rate);
+}
+
+/// Median of an ascending list of rates (mean of the middle pair when even)
+pub fn median(sorted_rates: &Vec<i128>) -> Option<i128> {
+    let len = sorted_rates.len();
+    if len == 0 {
+        return None;
+    }
+    let upper = sorted_rates.get(len / 2)?;
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+    let lower = sorted_rates.get(len / 2 - 1)?;
+    Some(lower + (upper - lower) / 2)
+}
+
+/// Validate a max oracle age against the configured bounds
+pub fn validate_max_age(max_age: u64, config: &OracleConfig) -> Result<(), &'static str> {
+    if max_age < config.min_age_bound || max_age > config.max_age_bound {
+        return Err("Max oracle age outside configured bounds");
+    }
+    Ok(())
+}
+
 /// Validate oracle data freshness
 pub fn validate_data_freshness(
     current_timestamp: u64,
@@ -46,3 +252,95 @@ pub fn validate_rate_bounds(rate: i128) -> Result<(), &'static str> {
     }
     Ok(())
 }
+
+/// Rescale a feed price with `decimals` places to `RATE_DECIMALS` places.
+/// Returns `None` on overflow.
+pub fn normalize_price(price: i128, decimals: u32) -> Option<i128> {
+    if decimals >= RATE_DECIMALS {
+        let divisor = 10i128.checked_pow(decimals - RATE_DECIMALS)?;
+        Some(price / divisor)
+    } else {
+        price.checked_mul(10i128.checked_pow(RATE_DECIMALS - decimals)?)
+    }
+}
+
+/// Realized volatility of a history in basis points: the root mean square of
+/// the returns between consecutive observations. Returns `None` with fewer
+/// than two observations.
+pub fn realized_volatility_bps(history: &Vec<PricePoint>) -> Option<u32> {
+    let mut sum_squares: i128 = 0;
+    let mut returns: i128 = 0;
+    let mut previous: Option<i128> = None;
+    for point in history.iter() {
+        if let Some(previous_rate) = previous.filter(|rate| *rate > 0) {
+            let change_bps = (point.rate - previous_rate).checked_mul(10000)? / previous_rate;
+            sum_squares = sum_squares.checked_add(change_bps.checked_mul(change_bps)?)?;
+            returns += 1;
+        }
+        previous = Some(point.rate);
+    }
+    if returns == 0 {
+        return None;
+    }
+    u32::try_from(isqrt(sum_squares / returns)).ok()
+}
+
+/// Integer square root (floor)
+fn isqrt(value: i128) -> i128 {
+    if value < 2 {
+        return value.max(0);
+    }
+    let mut x = value;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Latest observation at or before `timestamp` in an ascending history
+pub fn rate_at(history: &Vec<PricePoint>, timestamp: u64) -> Option<PricePoint> {
+    let mut found = None;
+    for point in history.iter() {
+        if point.timestamp > timestamp {
+            break;
+        }
+        found = Some(point);
+    }
+    found
+}
+
+/// Time-weighted average rate over `[now - window_secs, now]`.
+///
+/// Each observation holds from its timestamp until the next one (or `now`).
+/// Time in the window before the first observation is not counted. Returns
+/// `None` when there is no observation at or before `now`, or on overflow.
+pub fn twap(history: &Vec<PricePoint>, now: u64, window_secs: u64) -> Option<i128> {
+    let start = now.saturating_sub(window_secs);
+    let mut weighted_sum: i128 = 0;
+    let mut total_secs: u64 = 0;
+
+    let len = history.len();
+    for i in 0..len {
+        let point = history.get(i)?;
+        if point.timestamp > now {
+            break;
+        }
+        let until = match history.get(i + 1) {
+            Some(next) => next.timestamp.min(now),
+            None => now,
+        };
+        let from = point.timestamp.max(start);
+        if until > from {
+            let span = until - from;
+            weighted_sum = weighted_sum.checked_add(point.rate.checked_mul(span as i128)?)?;
+            total_secs += span;
+        }
+    }
+
+    if total_secs == 0 {
+        return rate_at(history, now).map(|point| point.rate);
+    }
+    Some(weighted_sum / total_secs as i128)
+}
@@ -1,280 +1,292 @@
 #[cfg(test)]
 mod test {
-    use crate::TimeLockContract;
-    use soroban_sdk::{Env, testutils::{Ledger as TestLedger, Address as TestAddress}};
-    use crate::types::{GiftStatus};
+    extern crate std;
+
+    use crate::{TimeLockContract, TimeLockContractClient};
+    use soroban_sdk::{vec, Env, Address, BytesN, String, token, testutils::{Ledger as TestLedger, Address as TestAddress}};
+    use ed25519_dalek::{Signer, SigningKey};
+    use rand::{rngs::OsRng, RngCore};
+    use crate::attestation::{self, AttestorProof, AttestorSignature, ClaimAttestation};
+    use crate::types::{ContractConfig, GiftStatus};
     use crate::constants;
     use crate::errors::Error;
+    use crate::fees;
+    use crate::slippage::{self, SlippageDirection};
 
-    #[test]
-    fn test_gift_creation() {
+    struct Setup<'a> {
+        env: Env,
+        client: TimeLockContractClient<'a>,
+        token: token::Client<'a>,
+        oracle_keypair: SigningKey,
+        sender: Address,
+        recipient: Address,
+        phone_hash: String,
+    }
+
+    fn setup<'a>() -> Setup<'a> {
         let env = Env::default();
+        env.mock_all_auths();
+
         let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let client = TimeLockContractClient::new(&env, &contract_id);
+
+        let oracle_keypair = SigningKey::generate(&mut OsRng);
+        let issuer = <Address as TestAddress>::generate(&env);
+        let token_address = env.register_stellar_asset_contract_v2(issuer).address();
+
+        client.initialize(&ContractConfig {
+            admin: <Address as TestAddress>::generate(&env),
+            attestation_pk: BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes()),
+            price_oracle: <Address as TestAddress>::generate(&env),
+            token: token_address.clone(),
+            fee_treasury: <Address as TestAddress>::generate(&env),
+        });
+
+        let sender = <Address as TestAddress>::generate(&env);
+        token::StellarAssetClient::new(&env, &token_address).mint(&sender, &constants::MAX_GIFT_AMOUNT);
+
+        Setup {
+            token: token::Client::new(&env, &token_address),
+            recipient: <Address as TestAddress>::generate(&env),
+            phone_hash: String::from_str(&env, "hash_of_phone_number"),
+            env,
+            client,
+            oracle_keypair,
+            sender,
+        }
+    }
+
+    fn proof_for(s: &Setup, gift_id: u64) -> ClaimAttestation {
+        let mut nonce = [0u8; 32];
+        OsRng.fill_bytes(&mut nonce);
+        let nonce = BytesN::from_array(&s.env, &nonce);
+        let expires_at = s.env.ledger().timestamp() + 600;
+
+        let payload = attestation::claim_payload(
+            &s.env,
+            &s.client.address,
+            gift_id,
+            &s.recipient,
+            &s.phone_hash,
+            expires_at,
+            &nonce,
+        );
+        let mut payload_vec = std::vec![0u8; payload.len() as usize];
+        payload.copy_into_slice(&mut payload_vec);
+
+        ClaimAttestation {
+            nonce,
+            expires_at,
+            signatures: vec![
+                &s.env,
+                AttestorSignature {
+                    key_id: 1,
+                    proof: AttestorProof::Ed25519(BytesN::from_array(&s.env, &s.oracle_keypair.sign(&payload_vec).to_bytes())),
+                },
+            ],
+        }
+    }
+
+    fn create_and_claim(s: &Setup, amount: i128, unlock_time: u64) -> u64 {
+        let gift_id = s.client.create_gift(&s.sender, &amount, &unlock_time, &s.phone_hash, &None);
+        s.client.claim_gift(&s.recipient, &gift_id, &proof_for(s, gift_id));
+        gift_id
+    }
+
+    #[test]
+    fn test_gift_creation() {
+        let s = setup();
+
         let current_time = 1000000u64;
-        env.ledger().set_timestamp(current_time);
-        
+        s.env.ledger().set_timestamp(current_time);
+
         let unlock_time = current_time + 3600;
         let amount = 10000000;
 
         // Test gift creation
-        let gift_id = env.as_contract(&contract_id, || {
-            TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                amount,
-                unlock_time,
-            ).unwrap()
-        });
-
-        assert_eq!(gift_id, 0);
+        let gift_id = s.client.create_gift(&s.sender, &amount, &unlock_time, &s.phone_hash, &None);
+        assert_eq!(gift_id, 1);
 
         // Test gift retrieval
-        let gift = env.as_contract(&contract_id, || {
-            TimeLockContract::get_gift(env.clone(), gift_id).unwrap()
-        });
-        assert_eq!(gift.sender, sender);
-        assert_eq!(gift.recipient, recipient);
-        assert_eq!(gift.amount, amount);
+        let gift = s.client.get_gift(&gift_id);
+        assert_eq!(gift.sender, s.sender);
+        assert_eq!(gift.recipient, None);
+        assert_eq!(gift.amount, amount - fees::calculate_fee(amount));
         assert_eq!(gift.unlock_timestamp, unlock_time);
         assert_eq!(gift.status, GiftStatus::Created);
     }
 
     #[test]
     fn test_gift_claim_flow() {
-        let env = Env::default();
-        let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let s = setup();
+
         let current_time = 1000000u64;
-        env.ledger().set_timestamp(current_time);
-        
+        s.env.ledger().set_timestamp(current_time);
+
         let unlock_time = current_time + 3600;
         let amount = 10000000;
 
         // Create gift
-        let gift_id = env.as_contract(&contract_id, || {
-            TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                amount,
-                unlock_time,
-            ).unwrap()
-        });
+        let gift_id = s.client.create_gift(&s.sender, &amount, &unlock_time, &s.phone_hash, &None);
 
         // Claim gift
-        env.as_contract(&contract_id, || {
-            TimeLockContract::claim_gift(env.clone(), gift_id, recipient.clone()).unwrap()
-        });
+        s.client.claim_gift(&s.recipient, &gift_id, &proof_for(&s, gift_id));
 
         // Verify gift is claimed
-        let gift = env.as_contract(&contract_id, || {
-            TimeLockContract::get_gift(env.clone(), gift_id).unwrap()
-        });
+        let gift = s.client.get_gift(&gift_id);
         assert_eq!(gift.status, GiftStatus::Claimed);
+        assert_eq!(gift.recipient, Some(s.recipient.clone()));
 
         // Try to claim again - should fail
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::claim_gift(env.clone(), gift_id, recipient.clone())
-        });
-        assert_eq!(result, Err(Error::AlreadyClaimed));
+        let result = s.client.try_claim_gift(&s.recipient, &gift_id, &proof_for(&s, gift_id));
+        assert_eq!(result.err(), Some(Ok(Error::AlreadyClaimed)));
     }
 
     #[test]
     fn test_unlock_time_boundary() {
-        let env = Env::default();
-        let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let s = setup();
+
         let current_time = 1000000u64;
-        env.ledger().set_timestamp(current_time);
-        
+        s.env.ledger().set_timestamp(current_time);
+
         let unlock_time = current_time + 3600;
         let amount = 10000000;
 
         // Create and claim gift
-        let gift_id = env.as_contract(&contract_id, || {
-            let id = TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                amount,
-                unlock_time,
-            ).unwrap();
-            TimeLockContract::claim_gift(env.clone(), id, recipient.clone()).unwrap();
-            id
-        });
+        let gift_id = create_and_claim(&s, amount, unlock_time);
 
         // Try to unlock before time - should fail
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::unlock_gift(env.clone(), gift_id, recipient.clone())
-        });
-        assert_eq!(result, Err(Error::UnlockTimeNotReached));
+        let result = s.client.try_unlock_gift(&gift_id, &s.recipient, &None, &None, &None);
+        assert_eq!(result.err(), Some(Ok(Error::UnlockTimeNotReached)));
 
         // Advance to exactly unlock time
-        env.ledger().set_timestamp(unlock_time);
-        
+        s.env.ledger().set_timestamp(unlock_time);
+
         // Should succeed
-        env.as_contract(&contract_id, || {
-            TimeLockContract::unlock_gift(env.clone(), gift_id, recipient.clone()).unwrap()
-        });
-        
-        let gift = env.as_contract(&contract_id, || {
-            TimeLockContract::get_gift(env.clone(), gift_id).unwrap()
-        });
+        s.client.unlock_gift(&gift_id, &s.recipient, &None, &None, &None);
+
+        let gift = s.client.get_gift(&gift_id);
         assert_eq!(gift.status, GiftStatus::Unlocked);
+        assert_eq!(s.token.balance(&s.recipient), gift.amount);
     }
 
     #[test]
     fn test_unlock_one_second_early_fails() {
-        let env = Env::default();
-        let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let s = setup();
+
         let current_time = 1000000u64;
-        env.ledger().set_timestamp(current_time);
-        
+        s.env.ledger().set_timestamp(current_time);
+
         let unlock_time = current_time + 3600;
         let amount = 10000000;
 
         // Create and claim gift
-        let gift_id = env.as_contract(&contract_id, || {
-            let id = TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                amount,
-                unlock_time,
-            ).unwrap();
-            TimeLockContract::claim_gift(env.clone(), id, recipient.clone()).unwrap();
-            id
-        });
+        let gift_id = create_and_claim(&s, amount, unlock_time);
 
         // Try to unlock 1 second early
-        env.ledger().set_timestamp(unlock_time - 1);
-        
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::unlock_gift(env.clone(), gift_id, recipient.clone())
-        });
-        assert_eq!(result, Err(Error::UnlockTimeNotReached));
+        s.env.ledger().set_timestamp(unlock_time - 1);
+
+        let result = s.client.try_unlock_gift(&gift_id, &s.recipient, &None, &None, &None);
+        assert_eq!(result.err(), Some(Ok(Error::UnlockTimeNotReached)));
+        assert_eq!(s.token.balance(&s.recipient), 0);
     }
 
     #[test]
     fn test_time_remaining_calculations() {
-        let env = Env::default();
-        let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let s = setup();
+
         let current_time = 1000000u64;
-        env.ledger().set_timestamp(current_time);
-        
+        s.env.ledger().set_timestamp(current_time);
+
         let unlock_time = current_time + 3600;
         let amount = 10000000;
 
         // Create and claim gift
-        let gift_id = env.as_contract(&contract_id, || {
-            let id = TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                amount,
-                unlock_time,
-            ).unwrap();
-            TimeLockContract::claim_gift(env.clone(), id, recipient.clone()).unwrap();
-            id
-        });
+        let gift_id = create_and_claim(&s, amount, unlock_time);
 
         // Check time remaining
-        let time_remaining = env.as_contract(&contract_id, || {
-            TimeLockContract::get_time_remaining(env.clone(), gift_id).unwrap()
-        });
-        assert_eq!(time_remaining, 3600);
+        assert_eq!(s.client.get_time_remaining(&gift_id), 3600);
 
         // Advance time by 1000 seconds
-        env.ledger().set_timestamp(current_time + 1000);
-        
-        let time_remaining = env.as_contract(&contract_id, || {
-            TimeLockContract::get_time_remaining(env.clone(), gift_id).unwrap()
-        });
-        assert_eq!(time_remaining, 2600);
+        s.env.ledger().set_timestamp(current_time + 1000);
+        assert_eq!(s.client.get_time_remaining(&gift_id), 2600);
 
         // Check can unlock
-        let can_unlock = env.as_contract(&contract_id, || {
-            TimeLockContract::can_unlock(env.clone(), gift_id).unwrap()
-        });
-        assert!(!can_unlock);
+        assert!(!s.client.can_unlock(&gift_id));
 
         // Advance past unlock time
-        env.ledger().set_timestamp(unlock_time + 1);
-        
-        let time_remaining = env.as_contract(&contract_id, || {
-            TimeLockContract::get_time_remaining(env.clone(), gift_id).unwrap()
-        });
-        assert_eq!(time_remaining, 0);
+        s.env.ledger().set_timestamp(unlock_time + 1);
+        assert_eq!(s.client.get_time_remaining(&gift_id), 0);
+        assert!(s.client.can_unlock(&gift_id));
 
-        let can_unlock = env.as_contract(&contract_id, || {
-            TimeLockContract::can_unlock(env.clone(), gift_id).unwrap()
-        });
-        assert!(can_unlock);
+        // Nothing left to unlock once funds are released
+        s.client.unlock_gift(&gift_id, &s.recipient, &None, &None, &None);
+        assert!(!s.client.can_unlock(&gift_id));
     }
 
     #[test]
     fn test_amount_validation() {
-        let env = Env::default();
-        let contract_id = env.register(TimeLockContract, ());
-        
-        let sender = <soroban_sdk::Address as TestAddress>::generate(&env);
-        let recipient = <soroban_sdk::Address as TestAddress>::generate(&env);
-        
+        let s = setup();
+
         let unlock_time = 1000000u64 + 3600;
 
         // Test amount too low
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                constants::MIN_GIFT_AMOUNT - 1,
-                unlock_time,
-            )
-        });
-        assert_eq!(result, Err(Error::InvalidAmount));
+        let result = s.client.try_create_gift(&s.sender, &(constants::MIN_GIFT_AMOUNT - 1), &unlock_time, &s.phone_hash, &None);
+        assert_eq!(result.err(), Some(Ok(Error::InvalidAmount)));
 
         // Test amount too high
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                constants::MAX_GIFT_AMOUNT + 1,
-                unlock_time,
-            )
-        });
-        assert_eq!(result, Err(Error::InvalidAmount));
+        let result = s.client.try_create_gift(&s.sender, &(constants::MAX_GIFT_AMOUNT + 1), &unlock_time, &s.phone_hash, &None);
+        assert_eq!(result.err(), Some(Ok(Error::InvalidAmount)));
 
         // Test valid amount
-        let result = env.as_contract(&contract_id, || {
-            TimeLockContract::create_gift(
-                env.clone(),
-                sender.clone(),
-                recipient.clone(),
-                constants::MIN_GIFT_AMOUNT,
-                unlock_time,
-            )
-        });
+        let result = s.client.try_create_gift(&s.sender, &constants::MIN_GIFT_AMOUNT, &unlock_time, &s.phone_hash, &None);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_slippage_direction() {
+        // A higher actual rate is favourable to the recipient
+        assert_eq!(slippage::slippage_direction(150), SlippageDirection::Favourable);
+        assert_eq!(slippage::slippage_direction(0), SlippageDirection::Favourable);
+        assert_eq!(slippage::slippage_direction(-150), SlippageDirection::Unfavourable);
+    }
+
+    #[test]
+    fn test_exceeded_slippage_limit() {
+        // Unbounded upside: only unfavourable moves are limited
+        assert_eq!(slippage::exceeded_slippage_limit(5000, 200, None), None);
+        assert_eq!(slippage::exceeded_slippage_limit(-200, 200, None), None);
+        assert_eq!(
+            slippage::exceeded_slippage_limit(-201, 200, None),
+            Some((SlippageDirection::Unfavourable, 200))
+        );
+
+        // Separate limits for each direction
+        assert_eq!(slippage::exceeded_slippage_limit(500, 200, Some(500)), None);
+        assert_eq!(
+            slippage::exceeded_slippage_limit(501, 200, Some(500)),
+            Some((SlippageDirection::Favourable, 500))
+        );
+    }
+
+    #[test]
+    fn test_validate_slippage_is_asymmetric() {
+        let s = setup();
+
+        // Upside is unbounded by default
+        s.client.validate_slippage(&1_000_000, &1_100_000);
+        let result = s.client.try_validate_slippage(&1_000_000, &970_000);
+        assert_eq!(result.err(), Some(Ok(Error::SlippageExceeded)));
+
+        // A favourable limit bounds upside moves separately
+        s.client.set_favourable_slippage(&Some(500));
+        assert_eq!(s.client.get_slippage_config(&String::from_str(&s.env, "USDC/NGN")).config.max_favourable_bps, Some(500));
+        s.client.validate_slippage(&1_000_000, &1_050_000);
+        let result = s.client.try_validate_slippage(&1_000_000, &1_100_000);
+        assert_eq!(result.err(), Some(Ok(Error::SlippageExceeded)));
+        s.client.validate_slippage(&1_000_000, &985_000);
+
+        let result = s.client.try_set_favourable_slippage(&Some(10_001));
+        assert_eq!(result.err(), Some(Ok(Error::InvalidSlippageConfig)));
+    }
 }
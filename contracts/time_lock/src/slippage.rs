@@ -1,12 +1,69 @@
 use soroban_sdk::contracttype;
-use soroban_sdk::Address;
+use soroban_sdk::{Address, String};
+
+use crate::fees;
+use crate::oracle::CircuitBreakerTrip;
 
 /// Slippage configuration
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SlippageConfig {
-    pub max_slippage_bps: u32,      // Maximum slippage in basis points (0-10000)
+    pub max_slippage_bps: u32,      // Maximum unfavourable slippage in basis points (0-10000)
     pub admin: Address,              // Admin address for configuration
+    pub twap_window_secs: u64,       // Compare against the TWAP over this window (0 = spot rate)
+    pub max_favourable_bps: Option<u32>, // Maximum favourable slippage (None = unbounded upside)
+    pub volatility_multiplier_bps: u32, // Volatility added to the base limit, in bps of itself (0 = static limit)
+    pub min_dynamic_bps: u32,        // Lower clamp of the volatility-adjusted limit
+    pub max_dynamic_bps: u32,        // Upper clamp of the volatility-adjusted limit
+}
+
+/// Slippage configuration with the limit currently in effect for a pair
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlippageStatus {
+    pub config: SlippageConfig,
+    pub pair: String,
+    pub volatility_bps: u32,        // Realized volatility of the pair's recorded rates
+    pub dynamic_bps: u32,           // Base limit adjusted for volatility and clamped
+    pub pair_bps: Option<u32>,      // Pair-specific limit, if any
+    pub effective_bps: u32,         // Unfavourable limit applied to the pair
+    pub binding_limit: SlippageLimit,
+}
+
+/// Direction of a rate move relative to the reference rate. Rates are
+/// quote-asset units per base-asset unit, so a higher rate is favourable
+/// to the recipient.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SlippageDirection {
+    Favourable,
+    Unfavourable,
+}
+
+/// Which setting supplied the effective slippage limit
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SlippageLimit {
+    Global,
+    Pair,
+    Gift,
+}
+
+/// Effective slippage limit: the tightest of the global, pair and gift
+/// settings. On a tie the most specific setting is reported as binding.
+pub fn effective_slippage_limit(
+    global_bps: u32,
+    pair_bps: Option<u32>,
+    gift_bps: Option<u32>,
+) -> (u32, SlippageLimit) {
+    let mut limit = (global_bps, SlippageLimit::Global);
+    if let Some(bps) = pair_bps.filter(|bps| *bps <= limit.0) {
+        limit = (bps, SlippageLimit::Pair);
+    }
+    if let Some(bps) = gift_bps.filter(|bps| *bps <= limit.0) {
+        limit = (bps, SlippageLimit::Gift);
+    }
+    limit
 }
 
 /// Default slippage configuration (2%)
@@ -14,6 +71,11 @@ pub fn default_slippage_config(admin: Address) -> SlippageConfig {
     SlippageConfig {
         max_slippage_bps: 200, // 2% default slippage
         admin,
+        twap_window_secs: 0,
+        max_favourable_bps: None,
+        volatility_multiplier_bps: 0,
+        min_dynamic_bps: 0,
+        max_dynamic_bps: 10000,
     }
 }
 
@@ -25,13 +87,33 @@ pub fn validate_slippage_bounds(slippage_bps: u32) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Validate the volatility-adjusted limit bounds
+pub fn validate_dynamic_bounds(min_bps: u32, max_bps: u32) -> Result<(), &'static str> {
+    validate_slippage_bounds(max_bps)?;
+    if min_bps > max_bps {
+        return Err("Minimum slippage exceeds maximum");
+    }
+    Ok(())
+}
+
+/// Base limit plus `volatility_multiplier_bps` of the realized volatility,
+/// clamped to the dynamic bounds. A zero multiplier keeps the static limit.
+pub fn dynamic_slippage_bps(config: &SlippageConfig, volatility_bps: u32) -> u32 {
+    if config.volatility_multiplier_bps == 0 {
+        return config.max_slippage_bps;
+    }
+    let scaled = volatility_bps as u64 * config.volatility_multiplier_bps as u64 / 10000;
+    (config.max_slippage_bps as u64 + scaled)
+        .clamp(config.min_dynamic_bps as u64, config.max_dynamic_bps as u64) as u32
+}
+
 /// Calculate expected output with slippage
 pub fn calculate_expected_output(
     oracle_rate: i128,
     amount: i128,
     max_slippage_bps: u32,
 ) -> i128 {
-    let output = (amount as i128)
+    let output = amount
         .checked_mul(oracle_rate)
         .unwrap_or(0)
         / 1_000_000;
@@ -44,11 +126,95 @@ pub fn calculate_expected_output(
     output - slippage_amount
 }
 
+/// Quote for converting an amount of a pair's base asset at the oracle rate,
+/// or for a gift's payout at unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quote {
+    pub amount: i128,       // Input amount in the base asset (e.g. USDC)
+    pub gross_output: i128, // Output in the quote asset at the rate, before fees
+    pub fee: i128,          // Protocol fee, in output units
+    pub min_output: i128,   // Least output after the fee and maximum slippage
+    pub rate: i128,         // Rate used, with 6 decimal precision
+    pub expires_at: u64,    // Last timestamp the quote may be relied on
+}
+
+/// Outcome of a quote request
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum QuoteOutcome {
+    Quoted(Quote),
+    Tripped(CircuitBreakerTrip),  // Rate query tripped the breaker; no quote was made
+}
+
+/// Build a quote for `amount` at `oracle_rate`, charging the protocol fee
+/// and allowing for up to `max_slippage_bps` of adverse movement
+pub fn build_quote(
+    amount: i128,
+    oracle_rate: i128,
+    max_slippage_bps: u32,
+    expires_at: u64,
+) -> Quote {
+    let gross_output = calculate_expected_output(oracle_rate, amount, 0);
+    let (net_amount, _) = fees::split_fee(amount);
+    Quote {
+        amount,
+        gross_output,
+        fee: fees::calculate_fee(gross_output),
+        min_output: calculate_expected_output(oracle_rate, net_amount, max_slippage_bps),
+        rate: oracle_rate,
+        expires_at,
+    }
+}
+
+/// Build a quote for paying out `amount` of the gift token at `rate`, in
+/// the unit the recipient receives. The protocol fee was charged when the
+/// gift was created, so none is deducted here.
+pub fn build_payout_quote(amount: i128, rate: i128, max_slippage_bps: u32, expires_at: u64) -> Quote {
+    Quote {
+        amount,
+        gross_output: calculate_expected_output(rate, amount, 0),
+        fee: 0,
+        min_output: calculate_expected_output(rate, amount, max_slippage_bps),
+        rate,
+        expires_at,
+    }
+}
+
+/// Direction of a signed rate difference (from `calculate_rate_difference`)
+pub fn slippage_direction(rate_diff: i128) -> SlippageDirection {
+    if rate_diff >= 0 {
+        SlippageDirection::Favourable
+    } else {
+        SlippageDirection::Unfavourable
+    }
+}
+
+/// Check a signed rate difference against per-direction limits. Returns the
+/// direction and the limit that was exceeded, or `None` if the move is
+/// within tolerance.
+pub fn exceeded_slippage_limit(
+    rate_diff: i128,
+    max_unfavourable_bps: u32,
+    max_favourable_bps: Option<u32>,
+) -> Option<(SlippageDirection, u32)> {
+    let direction = slippage_direction(rate_diff);
+    let limit = match direction {
+        SlippageDirection::Favourable => max_favourable_bps?,
+        SlippageDirection::Unfavourable => max_unfavourable_bps,
+    };
+    if rate_diff.abs() > limit as i128 {
+        Some((direction, limit))
+    } else {
+        None
+    }
+}
+
 /// Calculate percentage difference between two rates
 pub fn calculate_rate_difference(oracle_rate: i128, actual_rate: i128) -> i128 {
     if oracle_rate == 0 {
         return 0;
     }
-    let diff = (actual_rate as i128) - (oracle_rate as i128);
+    let diff = actual_rate - oracle_rate;
     (diff * 10000) / oracle_rate
 }
@@ -0,0 +1,87 @@
+use soroban_sdk::{
+    contractclient, contracterror, contracttype,
+    xdr::{ScErrorCode, ScErrorType},
+    Address, Env, InvokeError, String, Vec,
+};
+
+use crate::errors::Error;
+use crate::oracle::RATE_DECIMALS;
+
+/// Soroswap-style router interface used to convert payouts
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128>;
+}
+
+/// Error codes returned by the router
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum RouterError {
+    UnsupportedPath = 1,
+    InsufficientOutputAmount = 2, // Output is below the caller's minimum
+    InsufficientLiquidity = 3,
+    ExpiredDeadline = 4,
+}
+
+/// Contract error for a router failure the unlock can explain. Returns
+/// `None` for anything else, which the caller re-raises.
+///
+/// Only the error code is visible to the caller, so a failure with one of
+/// these codes from deeper in the call (e.g. a token contract's error 1) is
+/// mapped as if the router had returned it.
+pub fn map_router_error(err: &soroban_sdk::Error) -> Option<Error> {
+    if *err == RouterError::InsufficientOutputAmount.into() {
+        Some(Error::SlippageExceeded)
+    } else if *err == RouterError::InsufficientLiquidity.into() {
+        Some(Error::InsufficientLiquidity)
+    } else if *err == RouterError::UnsupportedPath.into() {
+        Some(Error::InvalidPayoutRoute)
+    } else {
+        None
+    }
+}
+
+/// Error value for a failed cross-contract call
+pub fn invoke_error(err: InvokeError) -> soroban_sdk::Error {
+    match err {
+        InvokeError::Contract(code) => soroban_sdk::Error::from_contract_error(code),
+        InvokeError::Abort => {
+            soroban_sdk::Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)
+        }
+    }
+}
+
+/// Route that converts the gift token into an alternative payout token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutRoute {
+    pub path: Vec<Address>,     // Router path from the gift token to the payout token
+    pub currency_pair: String,  // Pair whose oracle rate (payout per gift token) guards the swap
+}
+
+/// A route must start at the gift token, end at the payout token and have
+/// at least one hop
+pub fn validate_route(path: &Vec<Address>, gift_token: &Address, payout_token: &Address) -> bool {
+    path.len() >= 2
+        && path.first().as_ref() == Some(gift_token)
+        && path.last().as_ref() == Some(payout_token)
+        && gift_token != payout_token
+}
+
+/// Rate achieved by a swap, with `RATE_DECIMALS` precision
+pub fn executed_rate(amount_in: i128, amount_out: i128) -> Option<i128> {
+    if amount_in <= 0 {
+        return None;
+    }
+    amount_out
+        .checked_mul(10i128.pow(RATE_DECIMALS))
+        .map(|scaled| scaled / amount_in)
+}
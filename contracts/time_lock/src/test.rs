@@ -2,9 +2,243 @@
 extern crate std;
 
 use super::*;
-use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Bytes, BytesN, Env, String, xdr::ToXdr};
+use soroban_sdk::{testutils::{storage::{Instance as _, Persistent as _}, Address as _, Events, Ledger}, token, vec, IntoVal, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+use attestation::{AttestorPolicy, AttestorProof, AttestorPublicKey, AttestorSignature, ClaimAttestation};
+use p256::ecdsa::signature::hazmat::PrehashSigner;
+use zendvo_mock_oracle::{Asset as FeedAsset, MockOracle, MockOracleClient};
+use zendvo_mock_router::{MockRouter, MockRouterClient};
 use ed25519_dalek::{Signer, SigningKey};
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, RngCore};
+
+/// Register a Stellar Asset Contract to act as the gift token (USDC)
+fn create_token<'a>(env: &Env) -> (Address, token::Client<'a>, token::StellarAssetClient<'a>) {
+    let issuer = Address::generate(env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    let address = sac.address();
+    (
+        address.clone(),
+        token::Client::new(env, &address),
+        token::StellarAssetClient::new(env, &address),
+    )
+}
+
+/// Register a mock SEP-40 price feed with 14 decimals and a 300 second resolution
+fn create_price_feed<'a>(env: &Env) -> (Address, MockOracleClient<'a>) {
+    let address = env.register(MockOracle, ());
+    let feed = MockOracleClient::new(env, &address);
+    feed.configure(&14, &300);
+    (address, feed)
+}
+
+/// Generate a random attestation nonce
+fn random_nonce(env: &Env) -> BytesN<32> {
+    let mut nonce = [0u8; 32];
+    OsRng.fill_bytes(&mut nonce);
+    BytesN::from_array(env, &nonce)
+}
+
+/// Sign an arbitrary payload with an Ed25519 key
+fn sign_bytes(env: &Env, keypair: &SigningKey, payload: &Bytes) -> BytesN<64> {
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    BytesN::from_array(env, &keypair.sign(&payload_vec).to_bytes())
+}
+
+/// Attestor signing key for one of the supported signature schemes
+trait Attestor {
+    fn public_key(&self, env: &Env) -> AttestorPublicKey;
+    fn sign_payload(&self, env: &Env, payload: &Bytes) -> AttestorProof;
+}
+
+impl Attestor for SigningKey {
+    fn public_key(&self, env: &Env) -> AttestorPublicKey {
+        AttestorPublicKey::Ed25519(BytesN::from_array(env, &self.verifying_key().to_bytes()))
+    }
+
+    fn sign_payload(&self, env: &Env, payload: &Bytes) -> AttestorProof {
+        AttestorProof::Ed25519(sign_bytes(env, self, payload))
+    }
+}
+
+impl Attestor for p256::ecdsa::SigningKey {
+    fn public_key(&self, env: &Env) -> AttestorPublicKey {
+        let point = self.verifying_key().to_encoded_point(false);
+        AttestorPublicKey::Secp256r1(BytesN::from_array(env, point.as_bytes().try_into().unwrap()))
+    }
+
+    fn sign_payload(&self, env: &Env, payload: &Bytes) -> AttestorProof {
+        let digest = env.crypto().sha256(payload).to_array();
+        let signature: p256::ecdsa::Signature = self.sign_prehash(&digest).unwrap();
+        let signature = signature.normalize_s().unwrap_or(signature);
+        AttestorProof::Secp256r1(BytesN::from_array(env, &signature.to_bytes().into()))
+    }
+}
+
+impl Attestor for k256::ecdsa::SigningKey {
+    fn public_key(&self, env: &Env) -> AttestorPublicKey {
+        let point = self.verifying_key().to_encoded_point(false);
+        AttestorPublicKey::Secp256k1(BytesN::from_array(env, point.as_bytes().try_into().unwrap()))
+    }
+
+    fn sign_payload(&self, env: &Env, payload: &Bytes) -> AttestorProof {
+        let digest = env.crypto().sha256(payload).to_array();
+        let (signature, recovery_id) = self.sign_prehash_recoverable(&digest).unwrap();
+        AttestorProof::Secp256k1(
+            BytesN::from_array(env, &signature.to_bytes().into()),
+            recovery_id.to_byte() as u32,
+        )
+    }
+}
+
+/// Sign a claim attestation with an explicit expiry and nonce
+#[allow(clippy::too_many_arguments)]
+fn sign_attestation(
+    env: &Env,
+    keypair: &SigningKey,
+    contract_id: &Address,
+    gift_id: u64,
+    claimant: &Address,
+    phone_hash: &String,
+    expires_at: u64,
+    nonce: BytesN<32>,
+) -> ClaimAttestation {
+    let payload = attestation::claim_payload(
+        env, contract_id, gift_id, claimant, phone_hash, expires_at, &nonce,
+    );
+    ClaimAttestation {
+        nonce,
+        expires_at,
+        signatures: vec![
+            env,
+            AttestorSignature {
+                key_id: 1,
+                proof: keypair.sign_payload(env, &payload),
+            },
+        ],
+    }
+}
+
+/// Sign a claim attestation with several registered attestors, valid for the next hour
+fn sign_claim_with(
+    env: &Env,
+    attestors: &[(u32, &dyn Attestor)],
+    contract_id: &Address,
+    gift_id: u64,
+    claimant: &Address,
+    phone_hash: &String,
+) -> ClaimAttestation {
+    let nonce = random_nonce(env);
+    let expires_at = env.ledger().timestamp() + 3600;
+    let payload = attestation::claim_payload(
+        env, contract_id, gift_id, claimant, phone_hash, expires_at, &nonce,
+    );
+    let mut signatures = Vec::new(env);
+    for (key_id, keypair) in attestors {
+        signatures.push_back(AttestorSignature {
+            key_id: *key_id,
+            proof: keypair.sign_payload(env, &payload),
+        });
+    }
+    ClaimAttestation {
+        nonce,
+        expires_at,
+        signatures,
+    }
+}
+
+/// Sign a claim attestation for a gift, valid for the next hour
+fn sign_claim(
+    env: &Env,
+    keypair: &SigningKey,
+    contract_id: &Address,
+    gift_id: u64,
+    claimant: &Address,
+    phone_hash: &String,
+) -> ClaimAttestation {
+    sign_attestation(
+        env,
+        keypair,
+        contract_id,
+        gift_id,
+        claimant,
+        phone_hash,
+        env.ledger().timestamp() + 3600,
+        random_nonce(env),
+    )
+}
+
+/// Advance the ledger sequence and timestamp together
+fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|li| {
+        li.sequence_number += ledgers;
+        li.timestamp += ledgers as u64 * constants::LEDGER_SECONDS;
+    });
+}
+
+/// Initialize the contract with a fresh admin, price oracle and fee treasury
+fn initialize_contract(
+    env: &Env,
+    client: &TimeLockContractClient,
+    oracle_pk: &BytesN<32>,
+    token_address: &Address,
+) -> ContractConfig {
+    let config = ContractConfig {
+        admin: Address::generate(env),
+        attestation_pk: oracle_pk.clone(),
+        price_oracle: Address::generate(env),
+        token: token_address.clone(),
+        fee_treasury: Address::generate(env),
+    };
+    client.initialize(&config);
+    config
+}
+
+/// Id of a created fiat gift; fails the test on a circuit breaker trip
+fn fiat_gift_id(outcome: FiatGiftOutcome) -> u64 {
+    match outcome {
+        FiatGiftOutcome::Created(gift_id) => gift_id,
+        FiatGiftOutcome::Tripped(trip) => panic!("circuit breaker tripped: {:?}", trip),
+    }
+}
+
+/// Quote from a quote outcome; fails the test on a circuit breaker trip
+fn quoted(outcome: QuoteOutcome) -> slippage::Quote {
+    match outcome {
+        QuoteOutcome::Quoted(quote) => quote,
+        QuoteOutcome::Tripped(trip) => panic!("circuit breaker tripped: {:?}", trip),
+    }
+}
+
+#[test]
+fn test_initialize_and_get_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[7u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let res = client.try_get_config();
+    assert_eq!(res.err(), Some(Ok(Error::NotInitialized)));
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    let config = initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    assert_eq!(client.get_config(), config);
+    assert_eq!(client.get_oracle_status().oracle_sources, vec![&env, config.price_oracle.clone()]);
+    assert_eq!(client.get_slippage_config(&String::from_str(&env, "USDC/NGN")).config.admin, config.admin);
+
+    // Second initialization is rejected
+    let res = client.try_initialize(&config);
+    assert_eq!(res.err(), Some(Ok(Error::AlreadyInitialized)));
+
+    // Oracle address updates are reflected in the unified config
+    let new_oracle = Address::generate(&env);
+    client.set_oracle_address(&new_oracle);
+    assert_eq!(client.get_config().price_oracle, new_oracle);
+    assert_eq!(client.get_oracle_status().oracle_sources, vec![&env, new_oracle]);
+}
 
 #[test]
 fn test_claim_gift() {
@@ -20,61 +254,188 @@ fn test_claim_gift() {
     let contract_id = env.register(TimeLockContract, ());
     let client = TimeLockContractClient::new(&env, &contract_id);
 
-    client.initialize(&oracle_pk);
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
 
     // 2. Create Gift
     let sender = Address::generate(&env);
     let recipient_phone_hash = String::from_str(&env, "hash_of_phone_number");
     let amount = 10_000_000;
     let unlock_time = env.ledger().timestamp() + 100;
+    token_admin.mint(&sender, &amount);
 
     let gift_id = client.create_gift(
         &sender,
         &amount,
         &unlock_time,
         &recipient_phone_hash,
+        &None,
     );
 
+    // Funds are held in escrow by the contract
+    assert_eq!(token.balance(&sender), 0);
+    assert_eq!(token.balance(&contract_id), amount);
+
     // 3. Prepare Claim
     let claimant = Address::generate(&env);
     
-    // Construct payload for signature: claimant XDR + phone hash XDR
-    let mut payload = Bytes::new(&env);
-    payload.append(&claimant.clone().to_xdr(&env));
-    payload.append(&recipient_phone_hash.clone().to_xdr(&env));
-    
-    // Sign payload
-    let len = payload.len() as usize;
-    let mut payload_vec = std::vec![0u8; len];
-    payload.copy_into_slice(&mut payload_vec);
-    
-    let signature = oracle_keypair.sign(&payload_vec);
-    let signature_bytes = signature.to_bytes();
-    let proof = BytesN::from_array(&env, &signature_bytes);
-
-    // 4. Try to claim early (should fail)
-    let res = client.try_claim_gift(&claimant, &gift_id, &proof);
-    assert!(res.is_err());
-    assert_eq!(res.err(), Some(Ok(Error::NotUnlocked)));
+    // Sign a domain-separated attestation for this gift and claimant
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, gift_id, &claimant, &recipient_phone_hash);
 
-    // 5. Advance time
-    env.ledger().set_timestamp(unlock_time + 1);
+    // 4. Unlocking before claiming is rejected
+    let res = client.try_unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::GiftNotClaimed)));
 
-    // 6. Claim successfully
+    // 5. Claim before the unlock time binds the recipient, no funds move
     let res = client.try_claim_gift(&claimant, &gift_id, &proof);
     assert!(res.is_ok());
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, types::GiftStatus::Claimed);
+    assert_eq!(gift.recipient, Some(claimant.clone()));
+    assert_eq!(token.balance(&claimant), 0);
 
-    // 7. Try to claim again (should fail)
+    // 6. Try to claim again (should fail)
     let res = client.try_claim_gift(&claimant, &gift_id, &proof);
     assert_eq!(res.err(), Some(Ok(Error::AlreadyClaimed)));
+
+    // 7. Try to unlock early (should fail)
+    let res = client.try_unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::UnlockTimeNotReached)));
+    assert_eq!(token.balance(&claimant), 0);
+
+    // 8. Only the bound recipient can unlock
+    env.ledger().set_timestamp(unlock_time + 1);
+    let res = client.try_unlock_gift(&gift_id, &Address::generate(&env), &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::Unauthorized)));
+
+    // 9. Unlock successfully
+    client.unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    let fee = fees::calculate_fee(amount);
+    assert_eq!(token.balance(&claimant), amount - fee);
+    assert_eq!(token.balance(&contract_id), fee);
+    assert_eq!(client.get_gift(&gift_id).status, types::GiftStatus::Unlocked);
+
+    let res = client.try_unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::AlreadyUnlocked)));
+    assert_eq!(token.balance(&claimant), amount - fee);
 }
 
 #[test]
-#[should_panic]
-fn test_invalid_proof() {
+fn test_create_gift_insufficient_balance() {
     let env = Env::default();
     env.mock_all_auths();
-    
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    token_admin.mint(&sender, &(constants::MIN_GIFT_AMOUNT - 1));
+
+    // Sender cannot fund the escrow
+    let res = client.try_create_gift(
+        &sender,
+        &constants::MIN_GIFT_AMOUNT,
+        &(env.ledger().timestamp() + 100),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+    assert_eq!(res.err(), Some(Ok(Error::InsufficientBalance)));
+}
+
+#[test]
+fn test_create_gift_validation_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let unlock_time = env.ledger().timestamp() + 100;
+
+    // Uninitialized contract reports a typed error instead of panicking
+    let res = client.try_create_gift(&sender, &constants::MIN_GIFT_AMOUNT, &unlock_time, &phone_hash, &None);
+    assert_eq!(res.err(), Some(Ok(Error::NotInitialized)));
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    token_admin.mint(&sender, &constants::MAX_GIFT_AMOUNT);
+
+    let res = client.try_create_gift(&sender, &(constants::MIN_GIFT_AMOUNT - 1), &unlock_time, &phone_hash, &None);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAmount)));
+
+    let res = client.try_create_gift(&sender, &(constants::MAX_GIFT_AMOUNT + 1), &unlock_time, &phone_hash, &None);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAmount)));
+
+    let res = client.try_create_gift(&sender, &constants::MIN_GIFT_AMOUNT, &unlock_time, &phone_hash, &Some(unlock_time));
+    assert_eq!(res.err(), Some(Ok(Error::InvalidClaimDeadline)));
+
+    let proof = ClaimAttestation {
+        nonce: random_nonce(&env),
+        expires_at: env.ledger().timestamp(),
+        signatures: Vec::new(&env),
+    };
+    let res = client.try_claim_gift(&sender, &42, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::GiftNotFound)));
+}
+
+#[test]
+fn test_fee_collection_and_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    let config = initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 100),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+
+    // 2% of the deposit is held for the treasury
+    let expected_fee = 200_000;
+    assert_eq!(client.get_fee_balance(), expected_fee);
+    assert_eq!(token.balance(&contract_id), amount);
+
+    // Cannot withdraw more than has been collected
+    let treasury = config.fee_treasury;
+    let res = client.try_withdraw_fees(&(expected_fee + 1));
+    assert_eq!(res.err(), Some(Ok(Error::InsufficientFeeBalance)));
+
+    let res = client.try_withdraw_fees(&0);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAmount)));
+
+    client.withdraw_fees(&expected_fee);
+    assert_eq!(token.balance(&treasury), expected_fee);
+    assert_eq!(token.balance(&contract_id), amount - expected_fee);
+    assert_eq!(client.get_fee_balance(), 0);
+}
+
+#[test]
+fn test_refund_after_claim_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+
     let mut csprng = OsRng;
     let oracle_keypair = SigningKey::generate(&mut csprng);
     let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
@@ -82,62 +443,1975 @@ fn test_invalid_proof() {
     let contract_id = env.register(TimeLockContract, ());
     let client = TimeLockContractClient::new(&env, &contract_id);
 
-    client.initialize(&oracle_pk);
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
 
     let sender = Address::generate(&env);
     let recipient_phone_hash = String::from_str(&env, "hash_of_phone_number");
     let amount = 10_000_000;
-    let unlock_time = env.ledger().timestamp(); 
+    let unlock_time = env.ledger().timestamp() + 100;
+    let claim_deadline = unlock_time + 1_000;
+    token_admin.mint(&sender, &amount);
 
     let gift_id = client.create_gift(
         &sender,
         &amount,
         &unlock_time,
         &recipient_phone_hash,
+        &Some(claim_deadline),
     );
+    let net_amount = amount - fees::calculate_fee(amount);
 
+    // Refund is not available before the deadline
+    env.ledger().set_timestamp(claim_deadline);
+    let res = client.try_refund_gift(&gift_id);
+    assert_eq!(res.err(), Some(Ok(Error::RefundNotAvailable)));
+    let res = client.try_expire_gift(&gift_id);
+    assert_eq!(res.err(), Some(Ok(Error::RefundNotAvailable)));
+
+    // Past the deadline the gift can no longer be claimed
+    env.ledger().set_timestamp(claim_deadline + 1);
     let claimant = Address::generate(&env);
-    
-    // Wrong payload (different phone hash)
-    let wrong_hash = String::from_str(&env, "wrong_hash");
-    let mut payload = Bytes::new(&env);
-    payload.append(&claimant.clone().to_xdr(&env));
-    payload.append(&wrong_hash.clone().to_xdr(&env));
-    
-    let mut payload_vec = std::vec![0u8; payload.len() as usize];
-    payload.copy_into_slice(&mut payload_vec);
-    
-    let signature = oracle_keypair.sign(&payload_vec);
-    let proof = BytesN::from_array(&env, &signature.to_bytes());
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, gift_id, &claimant, &recipient_phone_hash);
 
-    // Should panic because of crypto verification failure
+    let res = client.try_claim_gift(&claimant, &gift_id, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::GiftExpired)));
+
+    client.expire_gift(&gift_id);
+    let res = client.try_claim_gift(&claimant, &gift_id, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::GiftExpired)));
+
+    // Sender recovers the escrowed amount; the fee stays with the treasury
+    client.refund_gift(&gift_id);
+    assert_eq!(token.balance(&sender), net_amount);
+    assert_eq!(token.balance(&contract_id), amount - net_amount);
+
+    let res = client.try_claim_gift(&claimant, &gift_id, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::GiftRefunded)));
+    let res = client.try_refund_gift(&gift_id);
+    assert_eq!(res.err(), Some(Ok(Error::GiftRefunded)));
+}
+
+#[test]
+fn test_refund_without_deadline_not_available() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 100),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+
+    env.ledger().set_timestamp(u64::MAX);
+    let res = client.try_refund_gift(&gift_id);
+    assert_eq!(res.err(), Some(Ok(Error::RefundNotAvailable)));
+}
+
+#[test]
+fn test_cancel_gift_with_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let res = client.try_set_cancellation_policy(&3_600, &10_001);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidCancellationPolicy)));
+    client.set_cancellation_policy(&3_600, &500);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "wrong_phone_hash"),
+        &None,
+    );
+    let protocol_fee = fees::calculate_fee(amount);
+    let net_amount = amount - protocol_fee;
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3_600);
+    client.cancel_gift(&gift_id);
+
+    // 5% cancellation fee is retained and added to the treasury balance
+    let cancellation_fee = fees::calculate_bps_fee(net_amount, 500);
+    assert_eq!(token.balance(&sender), net_amount - cancellation_fee);
+    assert_eq!(client.get_fee_balance(), protocol_fee + cancellation_fee);
+    assert_eq!(token.balance(&contract_id), protocol_fee + cancellation_fee);
+
+    let res = client.try_cancel_gift(&gift_id);
+    assert_eq!(res.err(), Some(Ok(Error::GiftCancelled)));
+}
+
+#[test]
+fn test_cancel_gift_window_closed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 2));
+    let start = env.ledger().timestamp();
+
+    // Outside the default 24h window, even though the gift is still locked
+    let long_lock = client.create_gift(
+        &sender,
+        &amount,
+        &(start + 7 * 86_400),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+    // Inside the window, but already unlocked
+    let short_lock = client.create_gift(
+        &sender,
+        &amount,
+        &(start + 60),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+
+    env.ledger().set_timestamp(start + constants::DEFAULT_CANCELLATION_WINDOW + 1);
+    let res = client.try_cancel_gift(&long_lock);
+    assert_eq!(res.err(), Some(Ok(Error::CancellationWindowClosed)));
+
+    env.ledger().set_timestamp(start + 60);
+    let res = client.try_cancel_gift(&short_lock);
+    assert_eq!(res.err(), Some(Ok(Error::CancellationWindowClosed)));
+}
+
+#[test]
+fn test_cancel_gift_after_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+    let start = env.ledger().timestamp();
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+
+    let gift_id = client.create_gift(&sender, &amount, &(start + 7 * 86_400), &phone_hash, &None);
+
+    // Whoever holds the phone hash claims immediately; the sender can still cancel
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, gift_id, &claimant, &phone_hash);
     client.claim_gift(&claimant, &gift_id, &proof);
-mod tests {
-    use crate::*;
+    client.cancel_gift(&gift_id);
 
-    #[test]
-    fn test_calculate_rate_difference() {
-        let diff = slippage::calculate_rate_difference(1000000, 1010000);
-        assert_eq!(diff, 100);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Cancelled);
+    assert_eq!(token.balance(&sender), gift.amount);
 
-        let diff = slippage::calculate_rate_difference(1000000, 1050000);
-        assert_eq!(diff, 500);
+    env.ledger().set_timestamp(start + 7 * 86_400);
+    let res = client.try_unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::GiftCancelled)));
+}
 
-        let diff = slippage::calculate_rate_difference(1000000, 990000);
-        assert_eq!(diff, -100);
-    }
+#[test]
+fn test_gift_stored_in_persistent_storage_with_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    #[test]
-    fn test_calculate_expected_output() {
-        let output = slippage::calculate_expected_output(1000000, 1000, 200);
-        assert_eq!(output, 980);
-    }
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
 
-    #[test]
-    fn test_validate_rate_bounds() {
-        assert!(oracle::validate_rate_bounds(1000000).is_ok());
-        assert!(oracle::validate_rate_bounds(0).is_err());
-        assert!(oracle::validate_rate_bounds(-1000000).is_err());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    // Birthday gift a year away
+    let year = 365 * 86_400;
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + year),
+        &String::from_str(&env, "hash_of_phone_number"),
+        &None,
+    );
+
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&DataKey::Gift(gift_id)));
+        assert!(env.storage().persistent().has(&DataKey::Gift(gift_id)));
+
+        // TTL covers the whole lock (capped at the network maximum)
+        let gift_ttl = env.storage().persistent().get_ttl(&DataKey::Gift(gift_id));
+        let ledgers_until_unlock = (year / constants::LEDGER_SECONDS) as u32;
+        assert!(gift_ttl >= ledgers_until_unlock.min(env.storage().max_ttl()));
+
+        assert!(env.storage().instance().get_ttl() >= constants::INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_long_lock_survives_past_default_ttls() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    let lock_days = 60;
+    let unlock_time = env.ledger().timestamp() + lock_days * 86_400;
+    let gift_id = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+
+    // Advance well past the default persistent TTL, the gift bump period and
+    // the instance bump period, poking the contract every few days. The token
+    // contract and the escrow balance it tracks are kept live separately, as
+    // they would be on the network.
+    let step = 5 * constants::DAY_IN_LEDGERS;
+    let escrow_balance_key = (Symbol::new(&env, "Balance"), contract_id.clone());
+    let mut elapsed = 0;
+    while elapsed < (lock_days as u32) * constants::DAY_IN_LEDGERS {
+        env.deployer()
+            .extend_ttl_for_contract_instance(token_address.clone(), step * 2, step * 2);
+        env.as_contract(&token_address, || {
+            env.storage()
+                .persistent()
+                .extend_ttl(&escrow_balance_key, step * 2, step * 2);
+        });
+        advance_ledgers(&env, step);
+        elapsed += step;
+        client.extend_gift_ttl(&gift_id);
+    }
+    assert!(elapsed > constants::GIFT_BUMP_AMOUNT);
+    assert!(env.ledger().timestamp() >= unlock_time);
+
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, gift_id, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_id, &proof);
+    client.unlock_gift(&gift_id, &claimant, &None, &None, &None);
+    assert_eq!(token.balance(&claimant), amount - fees::calculate_fee(amount));
+
+    let res = client.try_extend_gift_ttl(&999);
+    assert_eq!(res.err(), Some(Ok(Error::GiftNotFound)));
+}
+
+#[test]
+fn test_gift_query_views() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+
+    let current_time = 1_000_000u64;
+    env.ledger().set_timestamp(current_time);
+    let unlock_time = current_time + 3600;
+    let gift_id = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.sender, sender);
+    assert_eq!(gift.recipient, None);
+    assert_eq!(gift.amount, amount - fees::calculate_fee(amount));
+    assert_eq!(gift.unlock_timestamp, unlock_time);
+    assert_eq!(gift.created_at, current_time);
+    assert_eq!(gift.status, types::GiftStatus::Created);
+
+    assert_eq!(client.get_time_remaining(&gift_id), 3600);
+    assert!(!client.can_unlock(&gift_id));
+
+    env.ledger().set_timestamp(current_time + 1000);
+    assert_eq!(client.get_time_remaining(&gift_id), 2600);
+    assert!(!client.can_unlock(&gift_id));
+
+    // Unlock time passed, but the recipient has not claimed yet
+    env.ledger().set_timestamp(unlock_time + 1);
+    assert_eq!(client.get_time_remaining(&gift_id), 0);
+    assert!(!client.can_unlock(&gift_id));
+
+    assert_eq!(client.try_get_gift(&99).err(), Some(Ok(Error::GiftNotFound)));
+    assert_eq!(client.try_get_time_remaining(&99).err(), Some(Ok(Error::GiftNotFound)));
+    assert_eq!(client.try_can_unlock(&99).err(), Some(Ok(Error::GiftNotFound)));
+}
+
+#[test]
+fn test_gift_listings_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let other_sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let other_hash = String::from_str(&env, "other_phone_hash");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 5));
+    token_admin.mint(&other_sender, &amount);
+    let unlock_time = env.ledger().timestamp() + 100;
+
+    let mut sender_ids = std::vec::Vec::new();
+    for _ in 0..5 {
+        sender_ids.push(client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None));
+    }
+    let other_id = client.create_gift(&other_sender, &amount, &unlock_time, &other_hash, &None);
+
+    // First page, then follow the cursor
+    let page = client.get_gifts_by_sender(&sender, &0, &2);
+    assert_eq!(page.records.len(), 2);
+    assert_eq!(page.records.get(0).unwrap().gift_id, sender_ids[0]);
+    assert_eq!(page.records.get(1).unwrap().gift_id, sender_ids[1]);
+    assert_eq!(page.next_cursor, Some(2));
+
+    let page = client.get_gifts_by_sender(&sender, &page.next_cursor.unwrap(), &2);
+    assert_eq!(page.records.get(0).unwrap().gift_id, sender_ids[2]);
+    assert_eq!(page.next_cursor, Some(4));
+
+    let page = client.get_gifts_by_sender(&sender, &page.next_cursor.unwrap(), &2);
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.records.get(0).unwrap().gift_id, sender_ids[4]);
+    assert_eq!(page.next_cursor, None);
+
+    // A zero limit still reads one slot and keeps the cursor moving
+    let page = client.get_gifts_by_sender(&sender, &0, &0);
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.next_cursor, Some(1));
+
+    let page = client.get_gifts_by_sender(&other_sender, &0, &10);
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.records.get(0).unwrap().gift_id, other_id);
+
+    // Claimed gifts leave the phone hash listing but stay in the sender's
+    env.ledger().set_timestamp(unlock_time);
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, sender_ids[1], &claimant, &phone_hash);
+    client.claim_gift(&claimant, &sender_ids[1], &proof);
+
+    let page = client.get_gifts_by_phone_hash(&phone_hash, &0, &10);
+    assert_eq!(page.records.len(), 4);
+    assert!(page.records.iter().all(|record| record.gift_id != sender_ids[1]));
+
+    // Pages cover a fixed number of slots, skipping delisted gifts
+    let page = client.get_gifts_by_phone_hash(&phone_hash, &0, &2);
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.records.get(0).unwrap().gift_id, sender_ids[0]);
+    assert_eq!(page.next_cursor, Some(2));
+    assert_eq!(client.get_gifts_by_sender(&sender, &0, &10).records.len(), 5);
+
+    let page = client.get_gifts_by_phone_hash(&String::from_str(&env, "unknown"), &0, &10);
+    assert_eq!(page.records.len(), 0);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn test_attestation_replay_protection() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    env.ledger().set_timestamp(1_000_000);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 2));
+    let unlock_time = env.ledger().timestamp() + 100;
+    let first = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+    let second = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+
+    let claimant = Address::generate(&env);
+    let now = env.ledger().timestamp();
+
+    // Expired attestation
+    let proof = sign_attestation(
+        &env, &oracle_keypair, &contract_id, first, &claimant, &phone_hash, now - 1, random_nonce(&env),
+    );
+    let res = client.try_claim_gift(&claimant, &first, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationExpired)));
+
+    // Validity window longer than allowed
+    let proof = sign_attestation(
+        &env,
+        &oracle_keypair,
+        &contract_id,
+        first,
+        &claimant,
+        &phone_hash,
+        now + constants::MAX_ATTESTATION_VALIDITY + 1,
+        random_nonce(&env),
+    );
+    let res = client.try_claim_gift(&claimant, &first, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationValidityTooLong)));
+
+    // A nonce authorizes exactly one claim, even if re-signed for another gift
+    let nonce = random_nonce(&env);
+    let proof = sign_attestation(
+        &env, &oracle_keypair, &contract_id, first, &claimant, &phone_hash, now + 60, nonce.clone(),
+    );
+    client.claim_gift(&claimant, &first, &proof);
+
+    let proof = sign_attestation(
+        &env, &oracle_keypair, &contract_id, second, &claimant, &phone_hash, now + 60, nonce,
+    );
+    let res = client.try_claim_gift(&claimant, &second, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::NonceAlreadyUsed)));
+
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, second, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &second, &proof);
+}
+
+#[test]
+fn test_attestation_key_rotation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let old_keypair = SigningKey::generate(&mut OsRng);
+    let old_pk = BytesN::from_array(&env, &old_keypair.verifying_key().to_bytes());
+    let new_keypair = SigningKey::generate(&mut OsRng);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    env.ledger().set_timestamp(1_000_000);
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &old_pk, &token_address);
+
+    // The initial key is registered as key 1
+    let active = client.get_active_attestation_keys();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active.get(0).unwrap().key_id, 1);
+    assert_eq!(active.get(0).unwrap().key.public_key, old_keypair.public_key(&env));
+
+    let now = env.ledger().timestamp();
+    let res = client.try_add_attestation_key(&1, &new_keypair.public_key(&env), &(now + 100), &Some(now + 100));
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAttestationKey)));
+
+    let new_key_id = client.add_attestation_key(&1, &new_keypair.public_key(&env), &(now + 100), &Some(now + 10_000));
+    assert_eq!(new_key_id, 2);
+
+    // A public key that is still registered cannot be added again
+    let res = client.try_add_attestation_key(&2, &old_keypair.public_key(&env), &now, &None);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyExists)));
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 3));
+    let unlock_time = now + 100;
+    let first = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+    let second = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+    let third = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+    let claimant = Address::generate(&env);
+
+    // New key is not accepted before its activation time
+    let proof = sign_claim_with(&env, &[(new_key_id, &new_keypair)], &contract_id, first, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &first, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyInactive)));
+
+    let proof = sign_claim_with(&env, &[(9, &old_keypair)], &contract_id, first, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &first, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyNotFound)));
+
+    // Both keys are valid during the overlap window
+    env.ledger().set_timestamp(now + 100);
+    assert_eq!(client.get_active_attestation_keys().len(), 2);
+
+    let proof = sign_claim(&env, &old_keypair, &contract_id, first, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &first, &proof);
+
+    let proof = sign_claim_with(&env, &[(new_key_id, &new_keypair)], &contract_id, second, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &second, &proof);
+
+    // Revoked keys are rejected immediately
+    client.revoke_attestation_key(&1);
+    let active = client.get_active_attestation_keys();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active.get(0).unwrap().key_id, new_key_id);
+
+    let proof = sign_claim(&env, &old_keypair, &contract_id, third, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &third, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyInactive)));
+
+    // Keys stop verifying after their expiry
+    env.ledger().set_timestamp(now + 10_001);
+    assert_eq!(client.get_active_attestation_keys().len(), 0);
+    let proof = sign_claim_with(&env, &[(new_key_id, &new_keypair)], &contract_id, third, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &third, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyInactive)));
+
+    let res = client.try_revoke_attestation_key(&9);
+    assert_eq!(res.err(), Some(Ok(Error::AttestationKeyNotFound)));
+}
+
+#[test]
+fn test_attestor_threshold_for_high_value_claims() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let attestors = [
+        SigningKey::generate(&mut OsRng),
+        SigningKey::generate(&mut OsRng),
+        SigningKey::generate(&mut OsRng),
+    ];
+    let pk = |keypair: &SigningKey| BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &pk(&attestors[0]), &token_address);
+    client.add_attestation_key(&2, &attestors[1].public_key(&env), &env.ledger().timestamp(), &None);
+    client.add_attestation_key(&3, &attestors[2].public_key(&env), &env.ledger().timestamp(), &None);
+    // A rotated key for attestor 1 does not add a new attestor
+    let rotated = SigningKey::generate(&mut OsRng);
+    let rotated_id = client.add_attestation_key(&1, &rotated.public_key(&env), &env.ledger().timestamp(), &None);
+
+    // Single signer by default
+    let policy = client.get_attestor_policy();
+    assert_eq!(policy.threshold, 1);
+
+    let res = client.try_set_attestor_policy(&0, &50_000_000);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAttestorPolicy)));
+    let res = client.try_set_attestor_policy(&4, &50_000_000);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAttestorPolicy)));
+
+    client.set_attestor_policy(&2, &50_000_000);
+    assert_eq!(
+        client.get_attestor_policy(),
+        AttestorPolicy {
+            threshold: 2,
+            high_value_amount: 50_000_000,
+        }
+    );
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    token_admin.mint(&sender, &constants::MAX_GIFT_AMOUNT);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let small = client.create_gift(&sender, &10_000_000, &unlock_time, &phone_hash, &None);
+    let large = client.create_gift(&sender, &200_000_000, &unlock_time, &phone_hash, &None);
+    let claimant = Address::generate(&env);
+
+    // Small gifts keep single-signer verification
+    let proof = sign_claim_with(&env, &[], &contract_id, small, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &small, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::InsufficientAttestations)));
+
+    let proof = sign_claim_with(&env, &[(2, &attestors[1])], &contract_id, small, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &small, &proof);
+
+    // High-value gifts need signatures from distinct attestors
+    let proof = sign_claim_with(&env, &[(1, &attestors[0])], &contract_id, large, &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &large, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::InsufficientAttestations)));
+
+    let proof = sign_claim_with(
+        &env, &[(1, &attestors[0]), (1, &attestors[0])], &contract_id, large, &claimant, &phone_hash,
+    );
+    let res = client.try_claim_gift(&claimant, &large, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::DuplicateAttestor)));
+
+    let proof = sign_claim_with(
+        &env, &[(3, &attestors[2]), (1, &attestors[0])], &contract_id, large, &claimant, &phone_hash,
+    );
+    let res = client.try_claim_gift(&claimant, &large, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::DuplicateAttestor)));
+
+    // Old and rotated keys of one attestor count once
+    let proof = sign_claim_with(
+        &env, &[(1, &attestors[0]), (rotated_id, &rotated)], &contract_id, large, &claimant, &phone_hash,
+    );
+    let res = client.try_claim_gift(&claimant, &large, &proof);
+    assert_eq!(res.err(), Some(Ok(Error::DuplicateAttestor)));
+
+    let proof = sign_claim_with(
+        &env, &[(1, &attestors[0]), (3, &attestors[2])], &contract_id, large, &claimant, &phone_hash,
+    );
+    client.claim_gift(&claimant, &large, &proof);
+    assert_eq!(client.get_gift(&large).status, GiftStatus::Claimed);
+
+    // Expired keys do not count towards the attestors available
+    let now = env.ledger().timestamp();
+    let expiring = SigningKey::generate(&mut OsRng);
+    client.add_attestation_key(&4, &expiring.public_key(&env), &now, &Some(now + 10));
+    client.set_attestor_policy(&4, &50_000_000);
+    env.ledger().set_timestamp(now + 11);
+    let res = client.try_set_attestor_policy(&4, &50_000_000);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAttestorPolicy)));
+}
+
+#[test]
+#[should_panic]
+fn test_attestor_signature_from_wrong_key() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let other_keypair = SigningKey::generate(&mut OsRng);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    client.add_attestation_key(&2, &other_keypair.public_key(&env), &env.ledger().timestamp(), &None);
+    client.set_attestor_policy(&2, &0);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 100),
+        &phone_hash,
+        &None,
+    );
+
+    // One attestor cannot pose as two by signing under both key ids
+    let claimant = Address::generate(&env);
+    let proof = sign_claim_with(
+        &env, &[(1, &oracle_keypair), (2, &oracle_keypair)], &contract_id, gift_id, &claimant, &phone_hash,
+    );
+    client.claim_gift(&claimant, &gift_id, &proof);
+}
+
+#[test]
+fn test_attestation_signature_schemes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let ed25519_key = SigningKey::generate(&mut OsRng);
+    let secp256r1_key = p256::ecdsa::SigningKey::random(&mut OsRng);
+    let secp256k1_key = k256::ecdsa::SigningKey::random(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &ed25519_key.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let now = env.ledger().timestamp();
+    let secp256r1_id = client.add_attestation_key(&2, &secp256r1_key.public_key(&env), &now, &None);
+    let secp256k1_id = client.add_attestation_key(&3, &secp256k1_key.public_key(&env), &now, &None);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 4));
+    let unlock_time = now + 100;
+    let gift_ids: std::vec::Vec<u64> = (0..4)
+        .map(|_| client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None))
+        .collect();
+    let claimant = Address::generate(&env);
+
+    // Proof scheme must match the registered key
+    let proof = sign_claim_with(&env, &[(secp256r1_id, &ed25519_key)], &contract_id, gift_ids[0], &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &gift_ids[0], &proof);
+    assert_eq!(res.err(), Some(Ok(Error::SignatureSchemeMismatch)));
+
+    // secp256k1 recovery must yield the registered key
+    let impostor = k256::ecdsa::SigningKey::random(&mut OsRng);
+    let proof = sign_claim_with(&env, &[(secp256k1_id, &impostor)], &contract_id, gift_ids[0], &claimant, &phone_hash);
+    let res = client.try_claim_gift(&claimant, &gift_ids[0], &proof);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidProof)));
+
+    let proof = sign_claim_with(&env, &[(1, &ed25519_key)], &contract_id, gift_ids[0], &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_ids[0], &proof);
+
+    let proof = sign_claim_with(&env, &[(secp256r1_id, &secp256r1_key)], &contract_id, gift_ids[1], &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_ids[1], &proof);
+
+    let proof = sign_claim_with(&env, &[(secp256k1_id, &secp256k1_key)], &contract_id, gift_ids[2], &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_ids[2], &proof);
+
+    // Schemes can be mixed within one attestor set
+    client.set_attestor_policy(&3, &0);
+    let proof = sign_claim_with(
+        &env,
+        &[(1, &ed25519_key), (secp256r1_id, &secp256r1_key), (secp256k1_id, &secp256k1_key)],
+        &contract_id,
+        gift_ids[3],
+        &claimant,
+        &phone_hash,
+    );
+    client.claim_gift(&claimant, &gift_ids[3], &proof);
+
+    for gift_id in gift_ids {
+        assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Claimed);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_secp256r1_proof() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let secp256r1_key = p256::ecdsa::SigningKey::random(&mut OsRng);
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let key_id = client.add_attestation_key(&2, &secp256r1_key.public_key(&env), &env.ledger().timestamp(), &None);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 100),
+        &phone_hash,
+        &None,
+    );
+
+    // Signed with a different P-256 key than the one registered
+    let other_key = p256::ecdsa::SigningKey::random(&mut OsRng);
+    let claimant = Address::generate(&env);
+    let proof = sign_claim_with(&env, &[(key_id, &other_key)], &contract_id, gift_id, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_id, &proof);
+}
+
+#[test]
+#[should_panic]
+fn test_attestation_bound_to_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &(amount * 2));
+    let unlock_time = env.ledger().timestamp() + 100;
+    let first = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+    let second = client.create_gift(&sender, &amount, &unlock_time, &phone_hash, &None);
+
+    // Attestation for the first gift cannot claim the second one
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, first, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &second, &proof);
+}
+
+#[test]
+#[should_panic]
+fn test_attestation_bound_to_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    token_admin.mint(&sender, &amount);
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &(env.ledger().timestamp() + 100),
+        &phone_hash,
+        &None,
+    );
+
+    // Attestation issued for another deployment of the contract
+    let other_deployment = env.register(TimeLockContract, ());
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &other_deployment, gift_id, &claimant, &phone_hash);
+    client.claim_gift(&claimant, &gift_id, &proof);
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_proof() {
+    let env = Env::default();
+    env.mock_all_auths();
+    
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+
+    let sender = Address::generate(&env);
+    let recipient_phone_hash = String::from_str(&env, "hash_of_phone_number");
+    let amount = 10_000_000;
+    let unlock_time = env.ledger().timestamp(); 
+    token_admin.mint(&sender, &amount);
+
+    let gift_id = client.create_gift(
+        &sender,
+        &amount,
+        &unlock_time,
+        &recipient_phone_hash,
+        &None,
+    );
+
+    let claimant = Address::generate(&env);
+    
+    // Wrong payload (different phone hash)
+    let wrong_hash = String::from_str(&env, "wrong_hash");
+    let proof = sign_claim(&env, &oracle_keypair, &contract_id, gift_id, &claimant, &wrong_hash);
+
+    // Should panic because of crypto verification failure
+    client.claim_gift(&claimant, &gift_id, &proof);
+}
+
+#[test]
+fn test_check_exchange_rate_from_price_feed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    env.ledger().set_timestamp(1_000_000);
+    let now = env.ledger().timestamp();
+    let pair = String::from_str(&env, "USDC/NGN");
+
+    // Feed address that is not a contract
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OracleUnavailable)));
+
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+
+    // No price published yet
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OracleUnavailable)));
+
+    // 1.5 at 14 decimals is normalized to 6 decimals
+    feed.set_price(&ngn, &150_000_000_000_000, &now);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    // Cached until the feed's next update
+    feed.set_price(&ngn, &160_000_000_000_000, &(now + 10));
+    env.ledger().set_timestamp(now + 299);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    env.ledger().set_timestamp(now + 300);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_600_000));
+
+    // Feed prices older than the maximum age are rejected
+    env.ledger().set_timestamp(now + 10 + 301);
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::StaleOracleData)));
+
+    // Feeds with fewer decimals are scaled up
+    feed.configure(&2, &300);
+    feed.set_price(&ngn, &150, &env.ledger().timestamp());
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    // Non-positive prices are rejected
+    let ghs_pair = String::from_str(&env, "USDC/GHS");
+    client.set_currency_pair(&ghs_pair, &Asset::Other(Symbol::new(&env, "GHS")), &None);
+    feed.set_price(&FeedAsset::Other(Symbol::new(&env, "GHS")), &0, &env.ledger().timestamp());
+    let res = client.try_check_exchange_rate(&ghs_pair);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidExchangeRate)));
+
+    client.pause_oracle_checks();
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OraclePaused)));
+}
+
+#[test]
+fn test_currency_pair_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let now = env.ledger().timestamp();
+
+    let ngn_pair = String::from_str(&env, "USDC/NGN");
+    let ghs_pair = String::from_str(&env, "USDC/GHS");
+    let kes_pair = String::from_str(&env, "USDC/KES");
+
+    // Only USDC/NGN is supported out of the box
+    assert_eq!(client.get_supported_pairs(), vec![&env, ngn_pair.clone()]);
+    let res = client.try_check_exchange_rate(&ghs_pair);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+
+    client.set_currency_pair(&ghs_pair, &Asset::Other(Symbol::new(&env, "GHS")), &Some(60));
+    client.set_currency_pair(&kes_pair, &Asset::Other(Symbol::new(&env, "KES")), &None);
+    assert_eq!(client.get_supported_pairs().len(), 3);
+    assert_eq!(client.get_currency_pair(&ghs_pair).max_oracle_age, Some(60));
+
+    feed.set_price(&FeedAsset::Other(Symbol::new(&env, "NGN")), &150_000_000_000_000, &now);
+    feed.set_price(&FeedAsset::Other(Symbol::new(&env, "GHS")), &8_000_000_000_000, &now);
+    feed.set_price(&FeedAsset::Other(Symbol::new(&env, "KES")), &1_300_000_000_000_000, &now);
+
+    // Each pair is priced and cached independently
+    assert_eq!(client.check_exchange_rate(&ngn_pair), RateStatus::Fresh(1_500_000));
+    assert_eq!(client.check_exchange_rate(&ghs_pair), RateStatus::Fresh(80_000));
+    assert_eq!(client.check_exchange_rate(&kes_pair), RateStatus::Fresh(13_000_000));
+    assert_eq!(client.check_exchange_rate(&ngn_pair), RateStatus::Fresh(1_500_000));
+
+    // Per-pair max age overrides the oracle default
+    env.ledger().set_timestamp(now + 61);
+    let res = client.try_check_exchange_rate(&ghs_pair);
+    assert_eq!(res.err(), Some(Ok(Error::StaleOracleData)));
+    assert_eq!(client.check_exchange_rate(&ngn_pair), RateStatus::Fresh(1_500_000));
+
+    client.remove_currency_pair(&kes_pair);
+    let res = client.try_check_exchange_rate(&kes_pair);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+    let res = client.try_remove_currency_pair(&kes_pair);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+}
+
+#[test]
+fn test_median_of_multiple_oracle_sources() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    env.ledger().set_timestamp(1_000_000);
+    let now = env.ledger().timestamp();
+
+    let (feed_a, oracle_a) = create_price_feed(&env);
+    let (feed_b, oracle_b) = create_price_feed(&env);
+    let (feed_c, oracle_c) = create_price_feed(&env);
+    let sources = vec![&env, feed_a.clone(), feed_b.clone(), feed_c.clone()];
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+
+    // Quorum and deviation settings are validated
+    let res = client.try_set_oracle_sources(&sources, &0, &500);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleConfig)));
+    let res = client.try_set_oracle_sources(&sources, &4, &500);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleConfig)));
+    let res = client.try_set_oracle_sources(&vec![&env, feed_a.clone(), feed_a.clone()], &1, &500);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleConfig)));
+
+    client.set_oracle_sources(&sources, &2, &500);
+    let status = client.get_oracle_status();
+    assert_eq!(status.oracle_sources, sources);
+    assert_eq!(status.min_quorum, 2);
+
+    // Median of three sources
+    oracle_a.set_price(&ngn, &150_000_000_000_000, &now);
+    oracle_b.set_price(&ngn, &152_000_000_000_000, &now);
+    oracle_c.set_price(&ngn, &149_000_000_000_000, &now);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    // A stale source is dropped; the remaining two still meet quorum
+    env.ledger().set_timestamp(now + 301);
+    oracle_a.set_price(&ngn, &151_000_000_000_000, &(now + 301));
+    oracle_b.set_price(&ngn, &153_000_000_000_000, &(now + 301));
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_520_000));
+
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let queried: OracleRateQueried = data.into_val(&env);
+    assert_eq!(queried.sources, vec![&env, feed_a.clone(), feed_b.clone()]);
+    assert_eq!(queried.rate, 1_520_000);
+
+    // An unavailable source is dropped, and one valid response misses quorum
+    env.ledger().set_timestamp(now + 602);
+    oracle_a.set_price(&ngn, &151_000_000_000_000, &(now + 602));
+    oracle_b.clear_price(&ngn);
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OracleQuorumNotMet)));
+
+    // Sources that disagree by more than the allowed spread are rejected
+    oracle_b.set_price(&ngn, &170_000_000_000_000, &(now + 602));
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OracleDeviationExceeded)));
+
+    // With every source stale the staleness is reported
+    env.ledger().set_timestamp(now + 1_000);
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::StaleOracleData)));
+}
+
+#[test]
+fn test_price_history_and_twap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+
+    let res = client.try_get_twap(&pair, &600);
+    assert_eq!(res.err(), Some(Ok(Error::NoPriceHistory)));
+    let res = client.try_get_twap(&String::from_str(&env, "USDC/KES"), &600);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+
+    // Record 1.5 for 300s, then 1.6 for 300s
+    feed.set_price(&ngn, &150_000_000_000_000, &start);
+    client.check_exchange_rate(&pair);
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &160_000_000_000_000, &(start + 300));
+    client.check_exchange_rate(&pair);
+    // A cached read does not record a duplicate observation
+    client.check_exchange_rate(&pair);
+    env.ledger().set_timestamp(start + 600);
+
+    let history = client.get_price_history(&pair);
+    assert_eq!(history.len(), 2);
+    assert_eq!(client.get_twap(&pair, &600), 1_550_000);
+    assert_eq!(client.get_twap(&pair, &300), 1_600_000);
+    let res = client.try_get_twap(&pair, &0);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidTwapWindow)));
+
+    // Audit lookups return the rate in effect at a timestamp
+    assert_eq!(client.get_rate_at(&pair, &(start + 299)).rate, 1_500_000);
+    assert_eq!(client.get_rate_at(&pair, &(start + 300)).rate, 1_600_000);
+    let res = client.try_get_rate_at(&pair, &(start - 1));
+    assert_eq!(res.err(), Some(Ok(Error::NoPriceHistory)));
+
+    // Slippage checks can use the TWAP as the reference rate
+    feed.set_price(&ngn, &160_000_000_000_000, &(start + 600));
+    let res = client.try_validate_pair_slippage(&pair, &1_530_000);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    client.set_slippage_twap_window(&600);
+    client.validate_pair_slippage(&pair, &1_530_000);
+
+    // The ring buffer keeps only the most recent observations
+    for i in 1..=constants::PRICE_HISTORY_SIZE as u64 {
+        let ts = start + 600 + i * 301;
+        env.ledger().set_timestamp(ts);
+        feed.set_price(&ngn, &150_000_000_000_000, &ts);
+        client.check_exchange_rate(&pair);
+    }
+    let history = client.get_price_history(&pair);
+    assert_eq!(history.len(), constants::PRICE_HISTORY_SIZE);
+    assert_eq!(history.first().unwrap().timestamp, start + 600 + 301);
+}
+
+#[test]
+fn test_circuit_breaker_pauses_oracle_checks() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+    assert_eq!(client.get_oracle_status().circuit_breaker_bps, 1000);
+
+    feed.set_price(&ngn, &150_000_000_000_000, &start);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    // A 20% jump trips the breaker; the trip is reported, not a rate
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &180_000_000_000_000, &(start + 300));
+    let status = client.check_exchange_rate(&pair);
+
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let tripped: CircuitBreakerTripped = data.into_val(&env);
+    assert_eq!(tripped.new_rate, 1_800_000);
+    assert!(client.get_oracle_status().is_paused);
+
+    let trip = client.get_circuit_breaker_trip().unwrap();
+    assert_eq!(status, RateStatus::Tripped(trip.clone()));
+    assert_eq!(trip.previous_rate, 1_500_000);
+    assert_eq!(trip.new_rate, 1_800_000);
+    assert_eq!(trip.deviation_bps, 2000);
+    assert_eq!(trip.threshold_bps, 1000);
+
+    // FX-dependent operations refuse until an admin resumes
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::OraclePaused)));
+    let res = client.try_validate_pair_slippage(&pair, &1_800_000);
+    assert_eq!(res.err(), Some(Ok(Error::OraclePaused)));
+
+    // Resuming accepts the new level as the latest rate
+    client.resume_oracle_checks();
+    assert_eq!(client.get_circuit_breaker_trip(), None);
+    assert_eq!(client.get_rate_at(&pair, &(start + 300)).rate, 1_800_000);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_800_000));
+
+    // Operations that hit a trip report it instead of using the new rate,
+    // and the pause sticks
+    env.ledger().set_timestamp(start + 600);
+    feed.set_price(&ngn, &120_000_000_000_000, &(start + 600));
+    let status = client.validate_pair_slippage(&pair, &1_200_000);
+    let trip = client.get_circuit_breaker_trip().unwrap();
+    assert_eq!(status, RateStatus::Tripped(trip.clone()));
+    assert_eq!(trip.new_rate, 1_200_000);
+    assert!(client.get_oracle_status().is_paused);
+
+    // Disabled breaker lets large moves through
+    let res = client.try_set_circuit_breaker(&10_001);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleConfig)));
+    client.set_circuit_breaker(&0);
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let updated: CircuitBreakerUpdated = data.into_val(&env);
+    assert_eq!(updated.old_threshold_bps, 1000);
+    assert_eq!(updated.new_threshold_bps, 0);
+    client.resume_oracle_checks();
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_200_000));
+    assert!(!client.get_oracle_status().is_paused);
+}
+
+#[test]
+fn test_circuit_breaker_trip_during_unlock_persists() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    token_admin.mint(&sender, &200_000_000);
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+    feed.set_price(&ngn, &150_000_000_000_000_000, &start);
+    let gift_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &50_000_000_000, &(start + 100), &phone_hash, &None, &None));
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+
+    // A 20% jump at unlock trips the breaker: nothing is paid, but the
+    // pause and the trip record are kept
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &180_000_000_000_000_000, &(start + 300));
+    let outcome = client.unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert!(client.get_oracle_status().is_paused);
+    let trip = client.get_circuit_breaker_trip().unwrap();
+    assert_eq!(outcome, UnlockOutcome::Tripped(trip.clone()));
+    assert_eq!(trip.previous_rate, 1_500_000_000);
+    assert_eq!(trip.new_rate, 1_800_000_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Claimed);
+    assert_eq!(token.balance(&recipient), 0);
+
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::OraclePaused)));
+
+    // Once resumed, the gift settles at the new rate
+    client.resume_oracle_checks();
+    let outcome = client.unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert_eq!(outcome, UnlockOutcome::Unlocked(27_777_777));
+    assert_eq!(token.balance(&recipient), 27_777_777);
+}
+
+#[test]
+fn test_fiat_gift_settled_at_unlock_rate() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    token_admin.mint(&sender, &200_000_000);
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+    let fiat_amount = 50_000_000_000; // NGN 50,000
+
+    let res = client.try_create_fiat_gift(&sender, &pair, &0, &(start + 100), &phone_hash, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAmount)));
+    let usdc_kes = String::from_str(&env, "USDC/KES");
+    let res = client.try_create_fiat_gift(&sender, &usdc_kes, &fiat_amount, &(start + 100), &phone_hash, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+
+    // At 1,500 NGN per USDC the escrow covers 33.333334 USDC plus a 10% buffer,
+    // grossed up for the protocol fee
+    feed.set_price(&ngn, &150_000_000_000_000_000, &start);
+    let gift_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &fiat_amount, &(start + 100), &phone_hash, &None, &None));
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(token.balance(&sender), 200_000_000 - 37_414_967);
+    assert_eq!(gift.amount, 36_666_668);
+    let GiftDenomination::Fiat(denomination) = gift.denomination else {
+        panic!("expected a fiat-denominated gift");
+    };
+    assert_eq!(denomination.fiat_amount, fiat_amount);
+    assert_eq!(denomination.creation_rate, 1_500_000_000);
+
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+
+    // The naira weakens to 1,600: the recipient gets 31.25 USDC and the
+    // sender the rest of the escrow
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &160_000_000_000_000_000, &(start + 300));
+    let sender_before = token.balance(&sender);
+    client.unlock_gift(&gift_id, &recipient, &None, &None, &None);
+
+    let events = env.events().all();
+    let settled: FiatGiftSettled = events.get(events.len() - 2).unwrap().2.into_val(&env);
+    assert_eq!(settled.rate, 1_600_000_000);
+    assert_eq!(settled.payout, 31_250_000);
+    assert_eq!(settled.sender_refund, 36_666_668 - 31_250_000);
+    assert_eq!(token.balance(&recipient), 31_250_000);
+    assert_eq!(token.balance(&sender), sender_before + 36_666_668 - 31_250_000);
+
+    // The naira strengthening past the slippage limit of the creation rate
+    // (1,600 -> 1,200) refuses to settle during the grace period
+    let stuck_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &fiat_amount, &(start + 400), &phone_hash, &None, &None));
+    let stuck_escrow = client.get_gift(&stuck_id).amount;
+    let proof = sign_claim(&env, &keypair, &contract_id, stuck_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &stuck_id, &proof);
+    client.set_circuit_breaker(&0);
+    env.ledger().set_timestamp(start + 600);
+    feed.set_price(&ngn, &120_000_000_000_000_000, &(start + 600));
+    let recipient_before = token.balance(&recipient);
+    let res = client.try_unlock_gift(&stuck_id, &recipient, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+
+    // Even within a wide slippage limit, an escrow that cannot cover the
+    // fiat amount (41.666666 USDC owed against 31.25 USDC plus 10%) refuses
+    client.set_max_slippage(&3000);
+    let res = client.try_unlock_gift(&stuck_id, &recipient, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    assert_eq!(client.get_gift(&stuck_id).status, GiftStatus::Claimed);
+    assert_eq!(token.balance(&recipient), recipient_before);
+    client.set_max_slippage(&200);
+
+    // Settlement refuses a spot rate outside the slippage limit of the TWAP
+    let gift_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &fiat_amount, &(start + 700), &phone_hash, &None, &None));
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    client.set_slippage_twap_window(&600);
+    env.ledger().set_timestamp(start + 900);
+    feed.set_price(&ngn, &130_000_000_000_000_000, &(start + 900));
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Claimed);
+
+    // After the grace period the out-of-bound gift still settles: the
+    // recipient gets the whole escrow and the shortfall is reported
+    client.set_slippage_twap_window(&0);
+    let after_grace = start + 400 + constants::FIAT_SETTLEMENT_GRACE_SECS + 1;
+    env.ledger().set_timestamp(after_grace);
+    feed.set_price(&ngn, &120_000_000_000_000_000, &after_grace);
+    let quote = quoted(client.get_gift_quote(&stuck_id, &None));
+    assert_eq!(quote.gross_output, stuck_escrow);
+    client.unlock_gift(&stuck_id, &recipient, &None, &None, &None);
+
+    let events = env.events().all();
+    let capped: FiatSettlementCapped = events.get(events.len() - 3).unwrap().2.into_val(&env);
+    assert_eq!(capped.owed, 41_666_666);
+    assert_eq!(capped.payout, stuck_escrow);
+    assert_eq!(client.get_gift(&stuck_id).status, GiftStatus::Unlocked);
+    assert_eq!(token.balance(&recipient), recipient_before + stuck_escrow);
+}
+
+#[test]
+fn test_quote_and_min_out_at_unlock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    token_admin.mint(&sender, &100_000_000);
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+    feed.set_price(&ngn, &150_000_000_000_000_000, &start);
+
+    // 100 USDC at 1,500: 150,000 NGN gross, 2% fee, then 2% slippage on the net
+    let quote = quoted(client.get_quote(&100_000_000, &pair));
+    assert_eq!(quote.rate, 1_500_000_000);
+    assert_eq!(quote.gross_output, 150_000_000_000);
+    assert_eq!(quote.fee, 3_000_000_000);
+    assert_eq!(quote.min_output, 144_060_000_000);
+    assert_eq!(quote.expires_at, start + constants::QUOTE_VALIDITY_SECS);
+    let res = client.try_get_quote(&0, &pair);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidAmount)));
+
+    let gift_id = fiat_gift_id(client.create_fiat_gift(
+        &sender,
+        &pair,
+        &50_000_000_000,
+        &(start + 100),
+        &phone_hash,
+        &None,
+        &None,
+    ));
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+
+    // At 1,600 the settlement pays 31.25 USDC
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &160_000_000_000_000_000, &(start + 300));
+    let res = client.try_unlock_gift(&gift_id, &recipient, &Some(31_250_001), &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &Some(start + 299), &None);
+    assert_eq!(res.err(), Some(Ok(Error::QuoteExpired)));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Claimed);
+
+    client.unlock_gift(&gift_id, &recipient, &Some(31_250_000), &Some(start + 300), &None);
+    assert_eq!(token.balance(&recipient), 31_250_000);
+
+    // A gift quote is in the token paid out, without a fee, and can be
+    // passed straight into unlock_gift
+    let gift_id = fiat_gift_id(client.create_fiat_gift(
+        &sender,
+        &pair,
+        &50_000_000_000,
+        &(start + 400),
+        &phone_hash,
+        &None,
+        &None,
+    ));
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    env.ledger().set_timestamp(start + 600);
+    feed.set_price(&ngn, &158_000_000_000_000_000, &(start + 600));
+    let quote = quoted(client.get_gift_quote(&gift_id, &None));
+    assert_eq!(quote.gross_output, 31_645_569);
+    assert_eq!(quote.fee, 0);
+    assert_eq!(quote.min_output, 31_012_658);
+    assert_eq!(quote.expires_at, start + 600 + constants::QUOTE_VALIDITY_SECS);
+    client.unlock_gift(&gift_id, &recipient, &Some(quote.min_output), &Some(quote.expires_at), &None);
+    assert_eq!(token.balance(&recipient), 31_250_000 + 31_645_569);
+
+    // Token gifts pay out their escrow exactly
+    let gift_id = client.create_gift(&sender, &10_000_000, &(start + 700), &phone_hash, &None);
+    let escrow = client.get_gift(&gift_id).amount;
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    env.ledger().set_timestamp(start + 700);
+    let quote = quoted(client.get_gift_quote(&gift_id, &None));
+    assert_eq!(quote.min_output, escrow);
+    client.unlock_gift(&gift_id, &recipient, &Some(quote.min_output), &Some(quote.expires_at), &None);
+    assert_eq!(token.balance(&recipient), 31_250_000 + 31_645_569 + escrow);
+}
+
+#[test]
+fn test_pair_and_gift_slippage_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let sender = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    token_admin.mint(&sender, &200_000_000);
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+    feed.set_price(&ngn, &150_000_000_000_000_000, &start);
+
+    // A 1% drop is within the 2% global limit until the pair sets 0.5%
+    client.validate_pair_slippage(&pair, &1_485_000_000);
+    client.set_pair_slippage(&pair, &Some(50));
+    let res = client.try_validate_pair_slippage(&pair, &1_485_000_000);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    client.validate_pair_slippage(&pair, &1_493_000_000);
+
+    // The global check is unaffected, and pair updates keep the limit
+    client.validate_slippage(&1_500_000_000, &1_485_000_000);
+    client.set_currency_pair(&pair, &oracle::Asset::Other(Symbol::new(&env, "NGN")), &Some(600));
+    assert_eq!(client.get_currency_pair(&pair).max_slippage_bps, Some(50));
+
+    let res = client.try_set_pair_slippage(&pair, &Some(10_001));
+    assert_eq!(res.err(), Some(Ok(Error::InvalidSlippageConfig)));
+    let res = client.try_set_pair_slippage(&String::from_str(&env, "USDC/KES"), &Some(50));
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+    client.set_pair_slippage(&pair, &None);
+
+    // A spot rate 0.67% off the TWAP passes the global limit but not a
+    // sender's 0.5% gift tolerance
+    client.set_slippage_twap_window(&300);
+    env.ledger().set_timestamp(start + 300);
+    feed.set_price(&ngn, &149_000_000_000_000_000, &(start + 300));
+    let unlock_at = start + 400;
+    let res = client.try_create_fiat_gift(&sender, &pair, &50_000_000_000, &unlock_at, &phone_hash, &None, &Some(50));
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    let res = client.try_create_fiat_gift(&sender, &pair, &50_000_000_000, &unlock_at, &phone_hash, &None, &Some(10_001));
+    assert_eq!(res.err(), Some(Ok(Error::InvalidSlippageConfig)));
+
+    let gift_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &50_000_000_000, &unlock_at, &phone_hash, &None, &Some(100)));
+    let GiftDenomination::Fiat(denomination) = client.get_gift(&gift_id).denomination else {
+        panic!("expected a fiat-denominated gift");
+    };
+    assert_eq!(denomination.max_slippage_bps, Some(100));
+
+    // Without a TWAP window the gift tolerance still bounds settlement
+    // against the creation rate: a 1.07% drop passes the global limit only
+    let recipient = Address::generate(&env);
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    client.set_slippage_twap_window(&0);
+    env.ledger().set_timestamp(start + 600);
+    feed.set_price(&ngn, &147_400_000_000_000_000, &(start + 600));
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    feed.set_price(&ngn, &148_000_000_000_000_000, &(start + 600));
+    client.unlock_gift(&gift_id, &recipient, &None, &None, &None);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+}
+
+#[test]
+fn test_volatility_adjusted_slippage_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+
+    // Alternate between 1,500 and 1,530: roughly 2% moves each observation
+    let prices: [i128; 4] = [
+        150_000_000_000_000_000,
+        153_000_000_000_000_000,
+        150_000_000_000_000_000,
+        153_000_000_000_000_000,
+    ];
+    for (i, price) in prices.iter().enumerate() {
+        let ts = start + i as u64 * 300;
+        env.ledger().set_timestamp(ts);
+        feed.set_price(&ngn, price, &ts);
+        client.check_exchange_rate(&pair);
+    }
+
+    // Static limit until a multiplier is configured
+    let status = client.get_slippage_config(&pair);
+    assert_eq!(status.volatility_bps, 198);
+    assert_eq!(status.effective_bps, 200);
+    let res = client.try_validate_pair_slippage(&pair, &1_491_750_000);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+
+    // 1x volatility on top of the 2% base, clamped to 3%
+    client.set_dynamic_slippage(&10_000, &100, &300);
+    let status = client.get_slippage_config(&pair);
+    assert_eq!(status.dynamic_bps, 300);
+    assert_eq!(status.effective_bps, 300);
+    assert_eq!(status.binding_limit, slippage::SlippageLimit::Global);
+    client.validate_pair_slippage(&pair, &1_491_750_000);
+    let res = client.try_validate_pair_slippage(&pair, &1_476_450_000);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+
+    client.set_dynamic_slippage(&10_000, &100, &500);
+    assert_eq!(client.get_slippage_config(&pair).effective_bps, 398);
+
+    // A pair limit still tightens the dynamic one
+    client.set_pair_slippage(&pair, &Some(250));
+    let status = client.get_slippage_config(&pair);
+    assert_eq!(status.effective_bps, 250);
+    assert_eq!(status.binding_limit, slippage::SlippageLimit::Pair);
+
+    let res = client.try_set_dynamic_slippage(&10_000, &400, &300);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidSlippageConfig)));
+    let res = client.try_set_dynamic_slippage(&10_000, &100, &10_001);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidSlippageConfig)));
+}
+
+#[test]
+fn test_swap_payout_through_router() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let keypair = SigningKey::generate(&mut OsRng);
+    let oracle_pk = BytesN::from_array(&env, &keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (usdc_address, usdc, usdc_admin) = create_token(&env);
+    let (xlm_address, xlm_token, xlm_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &usdc_address);
+    let (feed_address, feed) = create_price_feed(&env);
+    client.set_oracle_address(&feed_address);
+    env.ledger().set_timestamp(1_000_000);
+    let start = env.ledger().timestamp();
+
+    let router_address = env.register(MockRouter, ());
+    let router = MockRouterClient::new(&env, &router_address);
+
+    // The oracle quotes 10 XLM per USDC
+    let pair = String::from_str(&env, "USDC/XLM");
+    client.set_currency_pair(&pair, &oracle::Asset::Other(Symbol::new(&env, "XLM")), &None);
+    let xlm_feed = FeedAsset::Other(Symbol::new(&env, "XLM"));
+    feed.set_price(&xlm_feed, &1_000_000_000_000_000, &(start + 100));
+
+    let path = vec![&env, usdc_address.clone(), xlm_address.clone()];
+    let res = client.try_set_payout_route(&xlm_address, &vec![&env, xlm_address.clone(), usdc_address.clone()], &pair);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidPayoutRoute)));
+    let res = client.try_set_payout_route(&xlm_address, &path, &String::from_str(&env, "USDC/KES"));
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedCurrencyPair)));
+    client.set_payout_route(&xlm_address, &path, &pair);
+    assert_eq!(client.get_payout_routes().len(), 1);
+
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let phone_hash = String::from_str(&env, "hash_of_phone_number");
+    usdc_admin.mint(&sender, &30_000_000);
+
+    let gift_id = client.create_gift(&sender, &10_000_000, &(start + 100), &phone_hash, &None);
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    env.ledger().set_timestamp(start + 100);
+
+    // Swaps need a router and a route for the payout token
+    let to_xlm = Some(xlm_address.clone());
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedPayoutToken)));
+    client.set_swap_router(&router_address);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &Some(Address::generate(&env)));
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedPayoutToken)));
+
+    // Router failures: too little liquidity, or a rate past the slippage limit
+    router.set_rate(&usdc_address, &xlm_address, &9_900_000);
+    xlm_admin.mint(&router_address, &50_000_000);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::InsufficientLiquidity)));
+    xlm_admin.mint(&router_address, &100_000_000);
+    router.set_rate(&usdc_address, &xlm_address, &9_500_000);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Claimed);
+
+    // Unexpected failures propagate instead of being reported as liquidity
+    let bogus = Address::generate(&env);
+    client.set_payout_route(&bogus, &vec![&env, usdc_address.clone(), bogus.clone()], &pair);
+    router.set_rate(&usdc_address, &bogus, &10_000_000);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &Some(bogus.clone()));
+    assert!(matches!(res, Err(Err(_))));
+    client.remove_payout_route(&bogus);
+
+    // Within the 2% limit the recipient receives XLM instead of USDC
+    router.set_rate(&usdc_address, &xlm_address, &9_900_000);
+    client.unlock_gift(&gift_id, &recipient, &Some(97_020_000), &None, &to_xlm);
+    assert_eq!(xlm_token.balance(&recipient), 97_020_000);
+    assert_eq!(usdc.balance(&recipient), 0);
+    assert_eq!(usdc.balance(&router_address), 9_800_000);
+
+    // A fiat gift's own tolerance (1%) bounds its payout swap
+    let xlm_amount = 50_000_000; // 50 XLM
+    let gift_id = fiat_gift_id(client.create_fiat_gift(&sender, &pair, &xlm_amount, &(start + 200), &phone_hash, &None, &Some(100)));
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    env.ledger().set_timestamp(start + 200);
+    router.set_rate(&usdc_address, &xlm_address, &9_850_000);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+    router.set_rate(&usdc_address, &xlm_address, &9_950_000);
+    let quote = quoted(client.get_gift_quote(&gift_id, &to_xlm));
+    assert_eq!(quote.gross_output, 50_000_000);
+    assert_eq!(quote.min_output, 49_500_000);
+    let recipient_before = xlm_token.balance(&recipient);
+    client.unlock_gift(&gift_id, &recipient, &Some(quote.min_output), &Some(quote.expires_at), &to_xlm);
+    assert_eq!(xlm_token.balance(&recipient), recipient_before + 49_750_000);
+
+    // The executed rate is also held to the favourable limit
+    let gift_id = client.create_gift(&sender, &10_000_000, &(start + 100), &phone_hash, &None);
+    let proof = sign_claim(&env, &keypair, &contract_id, gift_id, &recipient, &phone_hash);
+    client.claim_gift(&recipient, &gift_id, &proof);
+    client.set_favourable_slippage(&Some(100));
+    xlm_admin.mint(&router_address, &100_000_000);
+    router.set_rate(&usdc_address, &xlm_address, &10_500_000);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::SlippageExceeded)));
+
+    client.remove_payout_route(&xlm_address);
+    let res = client.try_unlock_gift(&gift_id, &recipient, &None, &None, &to_xlm);
+    assert_eq!(res.err(), Some(Ok(Error::UnsupportedPayoutToken)));
+}
+
+#[test]
+fn test_stale_oracle_data_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_pk = BytesN::from_array(&env, &[0u8; 32]);
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let (token_address, _token, _token_admin) = create_token(&env);
+    initialize_contract(&env, &client, &oracle_pk, &token_address);
+    env.ledger().set_timestamp(1_000_000);
+    let now = env.ledger().timestamp();
+
+    let (feed_a, oracle_a) = create_price_feed(&env);
+    let (feed_b, oracle_b) = create_price_feed(&env);
+    client.set_oracle_sources(&vec![&env, feed_a.clone(), feed_b.clone()], &1, &500);
+    let pair = String::from_str(&env, "USDC/NGN");
+    let ngn = FeedAsset::Other(Symbol::new(&env, "NGN"));
+
+    // Freshness uses each source's own timestamp, not the query time
+    oracle_a.set_price(&ngn, &150_000_000_000_000, &now);
+    oracle_b.set_price(&ngn, &151_000_000_000_000, &(now - 301));
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+
+    let events = env.events().all();
+    let stale: std::vec::Vec<OracleDataStale> = events
+        .iter()
+        .filter_map(|(_, topics, data)| {
+            let topic: Symbol = topics.get(0)?.into_val(&env);
+            (topic == symbol_short!("stale")).then(|| data.into_val(&env))
+        })
+        .collect();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].source, feed_b);
+    assert_eq!(stale[0].data_timestamp, now - 301);
+    assert_eq!(stale[0].max_age, 300);
+
+    // Exactly max age old is still fresh; one second more is stale
+    env.ledger().set_timestamp(now + 300);
+    oracle_a.set_price(&ngn, &150_000_000_000_000, &now);
+    assert_eq!(client.check_exchange_rate(&pair), RateStatus::Fresh(1_500_000));
+    env.ledger().set_timestamp(now + 301);
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::StaleOracleData)));
+
+    // Staleness is reported even when a later source is unavailable
+    let (feed_c, _oracle_c) = create_price_feed(&env);
+    client.set_oracle_sources(&vec![&env, feed_a.clone(), feed_c], &1, &500);
+    let res = client.try_check_exchange_rate(&pair);
+    assert_eq!(res.err(), Some(Ok(Error::StaleOracleData)));
+
+    // Max ages are bounded by the configured range
+    let res = client.try_set_max_oracle_age(&10);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleAge)));
+    let res = client.try_set_max_oracle_age(&7200);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleAge)));
+    let res = client.try_set_currency_pair(&pair, &Asset::Other(Symbol::new(&env, "NGN")), &Some(10));
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleAge)));
+
+    // Bounds must contain the configured ages
+    let res = client.try_set_oracle_age_bounds(&10, &100);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleAge)));
+    let res = client.try_set_oracle_age_bounds(&600, &60);
+    assert_eq!(res.err(), Some(Ok(Error::InvalidOracleAge)));
+
+    client.set_oracle_age_bounds(&10, &600);
+    client.set_max_oracle_age(&10);
+    let status = client.get_oracle_status();
+    assert_eq!(status.max_oracle_age, 10);
+    assert_eq!(status.min_age_bound, 10);
+    assert_eq!(status.max_age_bound, 600);
+}
+
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_calculate_rate_difference() {
+        let diff = slippage::calculate_rate_difference(1000000, 1010000);
+        assert_eq!(diff, 100);
+
+        let diff = slippage::calculate_rate_difference(1000000, 1050000);
+        assert_eq!(diff, 500);
+
+        let diff = slippage::calculate_rate_difference(1000000, 990000);
+        assert_eq!(diff, -100);
+    }
+
+    #[test]
+    fn test_calculate_expected_output() {
+        let output = slippage::calculate_expected_output(1000000, 1000, 200);
+        assert_eq!(output, 980);
+    }
+
+    #[test]
+    fn test_fiat_conversion_and_fee_gross_up() {
+        assert_eq!(oracle::fiat_to_token(50_000_000_000, 1_500_000_000, false), Some(33_333_333));
+        assert_eq!(oracle::fiat_to_token(50_000_000_000, 1_500_000_000, true), Some(33_333_334));
+        assert_eq!(oracle::fiat_to_token(1, 0, true), None);
+
+        let gross = fees::gross_for_net(36_666_667).unwrap();
+        assert_eq!(gross, 37_414_967);
+        assert!(fees::split_fee(gross).0 >= 36_666_667);
+    }
+
+    #[test]
+    fn test_effective_slippage_limit() {
+        use slippage::{effective_slippage_limit, SlippageLimit};
+
+        assert_eq!(effective_slippage_limit(200, None, None), (200, SlippageLimit::Global));
+        assert_eq!(effective_slippage_limit(200, Some(500), None), (200, SlippageLimit::Global));
+        assert_eq!(effective_slippage_limit(200, Some(100), None), (100, SlippageLimit::Pair));
+        assert_eq!(effective_slippage_limit(200, Some(100), Some(150)), (100, SlippageLimit::Pair));
+        assert_eq!(effective_slippage_limit(200, Some(100), Some(50)), (50, SlippageLimit::Gift));
+        assert_eq!(effective_slippage_limit(200, None, Some(200)), (200, SlippageLimit::Gift));
+    }
+
+    #[test]
+    fn test_realized_volatility_and_dynamic_limit() {
+        let env = Env::default();
+        let point = |rate: i128, timestamp: u64| PricePoint { rate, timestamp };
+
+        let calm = soroban_sdk::vec![&env, point(1_000_000, 0), point(1_000_000, 300)];
+        assert_eq!(oracle::realized_volatility_bps(&calm), Some(0));
+        let single = soroban_sdk::vec![&env, point(1_000_000, 0)];
+        assert_eq!(oracle::realized_volatility_bps(&single), None);
+
+        // Returns of +10% and -10% have an RMS of 10%
+        let swings = soroban_sdk::vec![&env, point(1_000_000, 0), point(1_100_000, 300), point(990_000, 600)];
+        assert_eq!(oracle::realized_volatility_bps(&swings), Some(1000));
+
+        let mut config = slippage::default_slippage_config(<Address as soroban_sdk::testutils::Address>::generate(&env));
+        assert_eq!(slippage::dynamic_slippage_bps(&config, 1000), 200);
+        config.volatility_multiplier_bps = 5000;
+        config.min_dynamic_bps = 250;
+        config.max_dynamic_bps = 1000;
+        assert_eq!(slippage::dynamic_slippage_bps(&config, 0), 250);
+        assert_eq!(slippage::dynamic_slippage_bps(&config, 400), 400);
+        assert_eq!(slippage::dynamic_slippage_bps(&config, 5000), 1000);
+    }
+
+    #[test]
+    fn test_split_fee() {
+        assert_eq!(fees::split_fee(10_000_000), (9_800_000, 200_000));
+        assert_eq!(fees::split_fee(5_000_000), (4_900_000, 100_000));
+        assert_eq!(fees::split_fee(49), (49, 0));
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        assert_eq!(Error::InvalidAmount as u32, 100);
+        assert_eq!(Error::GiftNotFound as u32, 101);
+        assert_eq!(Error::OracleUnavailable as u32, 200);
+        assert_eq!(Error::UnsupportedCurrencyPair as u32, 204);
+        assert_eq!(Error::SlippageExceeded as u32, 300);
+        assert_eq!(Error::QuoteExpired as u32, 302);
+        assert_eq!(Error::Unauthorized as u32, 400);
+        assert_eq!(Error::InsufficientLiquidity as u32, 500);
+        assert_eq!(Error::UnsupportedPayoutToken as u32, 503);
+    }
+
+    #[test]
+    fn test_validate_rate_bounds() {
+        assert!(oracle::validate_rate_bounds(1000000).is_ok());
+        assert!(oracle::validate_rate_bounds(0).is_err());
+        assert!(oracle::validate_rate_bounds(-1000000).is_err());
+    }
+
+    #[test]
+    fn test_normalize_price() {
+        assert_eq!(oracle::normalize_price(150_000_000_000_000, 14), Some(1_500_000));
+        assert_eq!(oracle::normalize_price(1_500_000, 6), Some(1_500_000));
+        assert_eq!(oracle::normalize_price(150, 2), Some(1_500_000));
+        assert_eq!(oracle::normalize_price(i128::MAX, 0), None);
+        assert_eq!(oracle::normalize_price(1, 60), None);
+    }
+
+    #[test]
+    fn test_median() {
+        let env = Env::default();
+        let mut rates = Vec::new(&env);
+        assert_eq!(oracle::median(&rates), None);
+
+        for rate in [1_500_000, 1_400_000, 1_600_000] {
+            oracle::insert_sorted(&mut rates, rate);
+        }
+        assert_eq!(rates, soroban_sdk::vec![&env, 1_400_000, 1_500_000, 1_600_000]);
+        assert_eq!(oracle::median(&rates), Some(1_500_000));
+
+        oracle::insert_sorted(&mut rates, 1_450_000);
+        assert_eq!(oracle::median(&rates), Some(1_475_000));
+    }
+
+    #[test]
+    fn test_twap_and_rate_at() {
+        let env = Env::default();
+        let point = |rate: i128, timestamp: u64| oracle::PricePoint { rate, timestamp };
+        let history = soroban_sdk::vec![&env, point(1_000_000, 100), point(2_000_000, 200), point(4_000_000, 400)];
+
+        assert_eq!(oracle::twap(&Vec::new(&env), 500, 100), None);
+        assert_eq!(oracle::twap(&history, 50, 100), None);
+        // 100s at 1.0, 200s at 2.0, 100s at 4.0
+        assert_eq!(oracle::twap(&history, 500, 400), Some(2_250_000));
+        // Time before the first observation is not counted
+        assert_eq!(oracle::twap(&history, 500, 10_000), Some(2_250_000));
+        assert_eq!(oracle::twap(&history, 500, 100), Some(4_000_000));
+        assert_eq!(oracle::twap(&history, 400, 0), Some(4_000_000));
+
+        assert_eq!(oracle::rate_at(&history, 99), None);
+        assert_eq!(oracle::rate_at(&history, 100), Some(point(1_000_000, 100)));
+        assert_eq!(oracle::rate_at(&history, 399), Some(point(2_000_000, 200)));
+    }
+
+    #[test]
+    fn test_validate_data_freshness() {
+        assert!(oracle::validate_data_freshness(1_300, 1_000, 300).is_ok());
+        assert!(oracle::validate_data_freshness(1_301, 1_000, 300).is_err());
+        assert!(oracle::validate_data_freshness(1_000, 1_100, 300).is_ok());
     }
 
     #[test]
@@ -1,18 +1,27 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
 
+use crate::oracle::CircuitBreakerTrip;
+
+/// Contract-wide configuration set once at initialization
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum GiftStatus {
-    Created = 0,
-    Claimed = 1,
+pub struct ContractConfig {
+    pub admin: Address,              // Admin for configuration and fee withdrawal
+    pub attestation_pk: BytesN<32>,  // Initial Ed25519 attestation key (registered as key id 1)
+    pub price_oracle: Address,       // Price oracle contract address
+    pub token: Address,              // SEP-41 gift token (USDC)
+    pub fee_treasury: Address,       // Account that receives fee withdrawals
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum GiftStatus {
     Created,
-    Claimed,
-    Unlocked,
+    Claimed,  // Recipient verified and bound, waiting for unlock time
+    Unlocked, // Funds released to the recipient
+    Expired,  // Claim deadline passed without a claim
+    Refunded, // Escrowed amount returned to the sender
+    Cancelled, // Withdrawn by the sender before unlock
 }
 
 #[contracttype]
@@ -20,8 +29,80 @@ pub enum GiftStatus {
 pub struct Gift {
     pub sender: Address,
     pub recipient: Option<Address>,
-    pub amount: i128,
+    pub amount: i128, // Net amount held for the recipient
+    pub fee: i128,    // Protocol fee deducted at creation
     pub unlock_timestamp: u64,
     pub recipient_phone_hash: String,
+    pub claim_deadline: Option<u64>, // Last timestamp the gift can be claimed
+    pub created_at: u64,
     pub status: GiftStatus,
+    pub denomination: GiftDenomination,
+}
+
+/// What a gift's recipient is owed at unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GiftDenomination {
+    Token,                  // The escrowed token amount
+    Fiat(FiatDenomination), // The token value of a fiat amount at the unlock-time rate
+}
+
+/// Fiat value of a gift, settled in the gift token at the unlock-time rate
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiatDenomination {
+    pub pair: String,        // Currency pair that prices the gift (e.g. "USDC/NGN")
+    pub fiat_amount: i128,   // Fiat owed to the recipient, with the gift token's decimals
+    pub creation_rate: i128, // Rate (fiat per token) the escrow was sized at
+    pub max_slippage_bps: Option<u32>, // Sender's tolerance for this gift (None = pair/global limit)
+}
+
+/// Outcome of `create_fiat_gift`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FiatGiftOutcome {
+    Created(u64),                 // Id of the new gift
+    Tripped(CircuitBreakerTrip),  // Rate query tripped the breaker; no gift was created
+}
+
+/// Outcome of `unlock_gift`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnlockOutcome {
+    Unlocked(i128),               // Amount paid to the recipient, in the token received
+    Tripped(CircuitBreakerTrip),  // Rate query tripped the breaker; the gift stays claimed
+}
+
+impl Gift {
+    /// Whether the claim deadline has passed at the given timestamp
+    pub fn is_past_deadline(&self, timestamp: u64) -> bool {
+        match self.claim_deadline {
+            Some(deadline) => timestamp > deadline,
+            None => false,
+        }
+    }
+}
+
+/// Rules for sender cancellation before unlock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationPolicy {
+    pub window_secs: u64, // Seconds after creation during which cancellation is allowed
+    pub fee_bps: u32,     // Fee retained on cancellation in basis points (0-10000)
+}
+
+/// A gift together with its id, as returned by listings
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftRecord {
+    pub gift_id: u64,
+    pub gift: Gift,
+}
+
+/// One page of a gift listing
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftPage {
+    pub records: Vec<GiftRecord>,
+    pub next_cursor: Option<u64>, // Pass as `cursor` to fetch the next page
 }